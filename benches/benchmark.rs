@@ -1,57 +1,213 @@
-#![feature(test)]
-extern crate test;
-
 use std::collections::HashMap;
-use test::Bencher;
+use std::time::Duration;
+
+use criterion::measurement::WallTime;
+use criterion::{
+    criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+
+use exercism_2022::exercises::anagram::anagram::anagrams_for;
+use exercism_2022::exercises::minesweeper::bitset::{self, BitBoard};
+use exercism_2022::exercises::minesweeper::minesweeper::annotate;
+use exercism_2022::exercises::parallel_letter_frequency::{self, Strategy};
+use exercism_2022::exercises::sublist::sublist::sublist;
+use exercism_2022::exercises::tournament::tournament::tally;
+
+/// Anthem repeats, so every size is a whole number of anthems.
+const SIZES: [usize; 3] = [1, 30, 300];
+const WORKER_COUNTS: [usize; 4] = [1, 2, 4, 8];
+
+fn letter_frequency_sizes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("letter_frequency");
+    for repeat in SIZES {
+        let texts = all_texts(repeat);
+        group.throughput(Throughput::Bytes(byte_len(&texts)));
+        for strategy in Strategy::VALUES {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{strategy:?}"), repeat),
+                &texts,
+                |b, texts| b.iter(|| parallel_letter_frequency::frequency_with(texts, 3, strategy)),
+            );
+        }
+        bench_rayon(&mut group, repeat, &texts);
+        group.bench_with_input(BenchmarkId::new("naive", repeat), &texts, |b, texts| {
+            b.iter(|| frequency(texts))
+        });
+    }
+    group.finish();
+}
+
+#[cfg(feature = "rayon")]
+fn bench_rayon(group: &mut BenchmarkGroup<WallTime>, repeat: usize, texts: &[&str]) {
+    use parallel_letter_frequency::par_iter;
+
+    group.bench_with_input(BenchmarkId::new("Rayon", repeat), texts, |b, texts| {
+        b.iter(|| par_iter::frequency(texts))
+    });
+}
+
+#[cfg(not(feature = "rayon"))]
+fn bench_rayon(_: &mut BenchmarkGroup<WallTime>, _: usize, _: &[&str]) {}
+
+// Same input, growing worker count: time per iteration should drop as long
+// as there are cores to spare.
+fn letter_frequency_workers(c: &mut Criterion) {
+    let texts = all_texts(300);
+    let mut group = c.benchmark_group("letter_frequency_workers");
+    group.throughput(Throughput::Bytes(byte_len(&texts)));
+    for strategy in Strategy::VALUES {
+        for worker_count in WORKER_COUNTS {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{strategy:?}"), worker_count),
+                &worker_count,
+                |b, &worker_count| {
+                    b.iter(|| {
+                        parallel_letter_frequency::frequency_with(&texts, worker_count, strategy)
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+// Single worker on plain ASCII, so the counting itself is measured rather
+// than the threading.
+fn letter_frequency_ascii(c: &mut Criterion) {
+    let texts = STAR_SPANGLED_BANNER.repeat(900);
+    let mut group = c.benchmark_group("letter_frequency_ascii");
+    group.throughput(Throughput::Bytes(byte_len(&texts)));
+    for strategy in [Strategy::Sequential, Strategy::Scoped, Strategy::Ascii] {
+        group.bench_function(format!("{strategy:?}"), |b| {
+            b.iter(|| parallel_letter_frequency::frequency_with(&texts, 1, strategy))
+        });
+    }
+    group.finish();
+}
 
-use exercism_2022::exercises::parallel_letter_frequency;
+fn sublists(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sublist");
+    for len in [1_000, 10_000, 100_000] {
+        let superlist = (0..len as u32).collect::<Vec<_>>();
+        // Found only at the very end, so the whole list is scanned.
+        let found = superlist[len - 10..].to_vec();
+        let missing = (len as u32 - 10..len as u32 + 1).collect::<Vec<_>>();
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::new("found", len), &found, |b, found| {
+            b.iter(|| sublist(found, &superlist))
+        });
+        group.bench_with_input(BenchmarkId::new("missing", len), &missing, |b, missing| {
+            b.iter(|| sublist(missing, &superlist))
+        });
+    }
+    group.finish();
+}
+
+fn anagrams(c: &mut Criterion) {
+    let mut group = c.benchmark_group("anagram");
+    for len in [10, 100, 1_000] {
+        let candidates = anagram_candidates("allergy", len);
+        let candidates = candidates.iter().map(String::as_str).collect::<Vec<_>>();
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(len),
+            &candidates,
+            |b, candidates| b.iter(|| anagrams_for("allergy", candidates)),
+        );
+    }
+    group.finish();
+}
 
-// #[bench]
-// fn bench_tiny_parallel(b: &mut Bencher) {
-//     let tiny = &["a"];
-//     b.iter(|| parallel_letter_frequency::frequency(tiny, 3));
-// }
+fn minesweeper(c: &mut Criterion) {
+    let mut group = c.benchmark_group("minesweeper");
+    for side in [10, 50, 200, 1_000] {
+        let board = minefield(side);
+        let board = board.iter().map(String::as_str).collect::<Vec<_>>();
+        group.throughput(Throughput::Elements((side * side) as u64));
+        group.bench_with_input(BenchmarkId::new("annotate", side), &board, |b, board| {
+            b.iter(|| annotate(board))
+        });
+        group.bench_with_input(BenchmarkId::new("bitset", side), &board, |b, board| {
+            b.iter(|| bitset::annotate(board))
+        });
+        bench_minesweeper_rayon(&mut group, side, &board);
 
-// #[bench]
-// fn bench_tiny_sequential(b: &mut Bencher) {
-//     let tiny = &["a"];
-//     b.iter(|| frequency(tiny));
-// }
+        // Counting alone, without parsing and building strings.
+        let bits = BitBoard::from_rows(&board);
+        group.bench_with_input(BenchmarkId::new("bitset_counts", side), &bits, |b, bits| {
+            b.iter(|| bits.counts())
+        });
+    }
+    group.finish();
+}
 
-// #[bench]
-// fn bench_small_parallel(b: &mut Bencher) {
-//     let texts = all_texts(1);
-//     b.iter(|| parallel_letter_frequency::frequency(&texts, 3));
-// }
+#[cfg(feature = "rayon")]
+fn bench_minesweeper_rayon(group: &mut BenchmarkGroup<WallTime>, side: usize, board: &[&str]) {
+    group.bench_with_input(BenchmarkId::new("bitset_rayon", side), board, |b, board| {
+        b.iter(|| bitset::par_annotate(board))
+    });
+}
 
-// #[bench]
-// fn bench_small_sequential(b: &mut Bencher) {
-//     let texts = all_texts(1);
-//     b.iter(|| frequency(&texts));
-// }
+#[cfg(not(feature = "rayon"))]
+fn bench_minesweeper_rayon(_: &mut BenchmarkGroup<WallTime>, _: usize, _: &[&str]) {}
 
-#[bench]
-fn bench_large_parallel_mutex(b: &mut Bencher) {
-    let texts = all_texts(30);
-    b.iter(|| parallel_letter_frequency::mutex::frequency(&texts, 3));
+fn tournament(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tournament");
+    for len in [10, 100, 1_000] {
+        let input = matches(len);
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &input, |b, input| {
+            b.iter(|| tally(input))
+        });
+    }
+    group.finish();
 }
 
-#[bench]
-fn bench_large_parallel_channels(b: &mut Bencher) {
-    let texts = all_texts(30);
-    b.iter(|| parallel_letter_frequency::frequency(&texts, 3));
+/// Rotations of `word` with a letter swapped in now and then, so about half
+/// of the candidates are anagrams.
+fn anagram_candidates(word: &str, len: usize) -> Vec<String> {
+    let word = word.chars().collect::<Vec<_>>();
+    (0..len)
+        .map(|index| {
+            let mut candidate = word.clone();
+            candidate.rotate_left(index % word.len());
+            if index % 2 == 1 {
+                candidate[0] = 'z';
+            }
+            candidate.into_iter().collect()
+        })
+        .collect()
 }
 
-#[bench]
-fn bench_large_parallel_scope(b: &mut Bencher) {
-    let texts = all_texts(30);
-    b.iter(|| parallel_letter_frequency::scope::frequency(&texts, 3));
+/// A `side` by `side` board with roughly one mine in five squares.
+fn minefield(side: usize) -> Vec<String> {
+    (0..side)
+        .map(|y| {
+            (0..side)
+                .map(|x| if (x * 7 + y * 13) % 5 == 0 { '*' } else { ' ' })
+                .collect()
+        })
+        .collect()
 }
 
-#[bench]
-fn bench_large_sequential(b: &mut Bencher) {
-    let texts = all_texts(30);
-    b.iter(|| frequency(&texts));
+fn matches(len: usize) -> String {
+    const TEAMS: [&str; 4] = [
+        "Allegoric Alaskans",
+        "Blithering Badgers",
+        "Courageous Californians",
+        "Devastating Donkeys",
+    ];
+    const RESULTS: [&str; 3] = ["win", "draw", "loss"];
+    (0..len)
+        .map(|index| {
+            let home = TEAMS[index % TEAMS.len()];
+            // Never the home team itself.
+            let offset = 1 + (index / TEAMS.len()) % (TEAMS.len() - 1);
+            let away = TEAMS[(index + offset) % TEAMS.len()];
+            format!("{home};{away};{}", RESULTS[index % RESULTS.len()])
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 /// Simple sequential char frequency. Can it be beat?
@@ -78,6 +234,10 @@ fn all_texts(repeat: usize) -> Vec<&'static str> {
         .collect()
 }
 
+fn byte_len(texts: &[&str]) -> u64 {
+    texts.iter().map(|line| line.len() as u64).sum()
+}
+
 // Poem by Friedrich Schiller. The corresponding music is the European Anthem.
 pub const ODE_AN_DIE_FREUDE: [&str; 8] = [
     "Freude schöner Götterfunken",
@@ -113,3 +273,23 @@ pub const STAR_SPANGLED_BANNER: [&str; 8] = [
     "O say does that star-spangled banner yet wave,",
     "O'er the land of the free and the home of the brave?",
 ];
+
+fn config() -> Criterion {
+    // There are a lot of combinations; keep a full run in minutes.
+    Criterion::default()
+        .sample_size(20)
+        .warm_up_time(Duration::from_millis(500))
+        .measurement_time(Duration::from_secs(2))
+}
+
+criterion_group! {
+    name = letter_frequency;
+    config = config();
+    targets = letter_frequency_sizes, letter_frequency_workers, letter_frequency_ascii
+}
+criterion_group! {
+    name = exercises;
+    config = config();
+    targets = sublists, anagrams, minesweeper, tournament
+}
+criterion_main!(letter_frequency, exercises);
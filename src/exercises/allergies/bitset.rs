@@ -0,0 +1,223 @@
+use std::fmt;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A set of small numbers, one bit each, growing as large ones go in.
+///
+/// Trailing words of zeros are always dropped, so two sets with the same
+/// members are equal and hash the same however they got there.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    /// Bit `i` of `words[w]` is `64 * w + i`.
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The set of the bits of `word`: bit `i` set means `i` is in.
+    pub fn from_word(word: u64) -> Self {
+        let mut set = Self { words: vec![word] };
+        set.trim();
+        set
+    }
+
+    /// The members below 64 as the bits of a word, the inverse of
+    /// [`from_word`](Self::from_word). `None` if there are larger ones.
+    pub fn to_word(&self) -> Option<u64> {
+        match self.words[..] {
+            [] => Some(0),
+            [word] => Some(word),
+            _ => None,
+        }
+    }
+
+    /// Whether `value` was new.
+    pub fn insert(&mut self, value: usize) -> bool {
+        let (word, bit) = (value / BITS, value % BITS);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let new = self.words[word] & 1 << bit == 0;
+        self.words[word] |= 1 << bit;
+        new
+    }
+
+    /// Whether `value` was there.
+    pub fn remove(&mut self, value: usize) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        self.words[value / BITS] &= !(1 << (value % BITS));
+        self.trim();
+        true
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.words
+            .get(value / BITS)
+            .is_some_and(|word| word & 1 << (value % BITS) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// One past the largest member, 0 for an empty set.
+    pub fn bound(&self) -> usize {
+        self.words.last().map_or(0, |word| {
+            (self.words.len() - 1) * BITS + (BITS - word.leading_zeros() as usize)
+        })
+    }
+
+    /// The members, smallest first.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            words: &self.words,
+            index: 0,
+            word: self.words.first().copied().unwrap_or(0),
+        }
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.words.truncate(other.words.len());
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= other;
+        }
+        self.trim();
+    }
+
+    pub fn difference_with(&mut self, other: &Self) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= !other;
+        }
+        self.trim();
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words.len() <= other.words.len()
+            && self
+                .words
+                .iter()
+                .zip(&other.words)
+                .all(|(word, other)| word & !other == 0)
+    }
+
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+}
+
+impl fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a> {
+    words: &'a [u64],
+    index: usize,
+    /// What's left of `words[index]`.
+    word: u64,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.word == 0 {
+            self.index += 1;
+            self.word = *self.words.get(self.index)?;
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some(self.index * BITS + bit)
+    }
+}
+
+#[test]
+fn grows_and_shrinks() {
+    let mut set = BitSet::new();
+    assert!(set.insert(3));
+    assert!(set.insert(200));
+    assert!(!set.insert(3));
+    assert_eq!(set.iter().collect::<Vec<_>>(), [3, 200]);
+    assert_eq!((set.len(), set.bound()), (2, 201));
+    assert_eq!(set.to_word(), None);
+
+    assert!(set.remove(200));
+    assert!(!set.remove(200));
+    assert_eq!(set, BitSet::from_word(0b1000));
+    assert_eq!(set.to_word(), Some(8));
+    set.remove(3);
+    assert!(set.is_empty());
+    assert_eq!((set.bound(), set.to_word()), (0, Some(0)));
+}
+
+#[test]
+fn set_operations() {
+    let a = [1, 64, 65, 130].into_iter().collect::<BitSet>();
+    let b = [1, 65, 300].into_iter().collect::<BitSet>();
+
+    let mut union = a.clone();
+    union.union_with(&b);
+    assert_eq!(union, [1, 64, 65, 130, 300].into_iter().collect());
+
+    let mut both = a.clone();
+    both.intersect_with(&b);
+    assert_eq!(both, [1, 65].into_iter().collect());
+    assert!(both.is_subset(&a) && both.is_subset(&b));
+    assert!(!a.is_subset(&b));
+
+    let mut only_a = a.clone();
+    only_a.difference_with(&b);
+    assert_eq!(only_a, [64, 130].into_iter().collect());
+    only_a.difference_with(&a);
+    assert_eq!(only_a, BitSet::new());
+    assert_eq!(format!("{a:?}"), "{1, 64, 65, 130}");
+}
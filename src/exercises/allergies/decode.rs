@@ -0,0 +1,177 @@
+use std::fmt;
+
+use super::{Allergen, Allergies};
+
+/// What [`Allergies::decode`] does with bits no [`Allergen`] has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Reject the score: it most likely comes from a newer list of
+    /// allergens than this one.
+    #[default]
+    Strict,
+    /// Drop them.
+    Lenient,
+}
+
+/// A score with bits set that no [`Allergen`] has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownBits {
+    score: u32,
+}
+
+impl UnknownBits {
+    /// The score as it was decoded.
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Only the unknown bits of the score.
+    pub fn unknown(&self) -> u32 {
+        self.score & !Allergies::KNOWN
+    }
+
+    /// The positions of the unknown bits, lowest first.
+    pub fn positions(&self) -> impl Iterator<Item = u32> {
+        let unknown = self.unknown();
+        (0..u32::BITS).filter(move |bit| unknown >> bit & 1 == 1)
+    }
+
+    /// The allergies the score does have, as [`Mode::Lenient`] decodes it.
+    pub fn known(&self) -> Allergies {
+        Allergies::new(self.score & Allergies::KNOWN)
+    }
+}
+
+impl fmt::Display for UnknownBits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "score {} has unknown allergen bits", self.score)?;
+        for (index, bit) in self.positions().enumerate() {
+            let separator = if index == 0 { " " } else { ", " };
+            write!(f, "{separator}{bit}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownBits {}
+
+impl Allergies {
+    /// The bits of every [`Allergen`], worked out from [`Allergen::VALUES`]
+    /// so a new one is never taken for an unknown bit.
+    pub const KNOWN: u32 = {
+        let mut known = 0;
+        let mut index = 0;
+        while index < Allergen::VALUES.len() {
+            known |= Allergen::VALUES[index] as u32;
+            index += 1;
+        }
+        known
+    };
+
+    /// Read a score from elsewhere. Unlike [`new`](Self::new), never keeps
+    /// bits that aren't an [`Allergen`]'s: they're an error, or dropped in
+    /// [`Mode::Lenient`].
+    pub fn decode(score: u32, mode: Mode) -> Result<Self, UnknownBits> {
+        let error = UnknownBits { score };
+        match mode {
+            Mode::Strict if error.unknown() != 0 => Err(error),
+            _ => Ok(error.known()),
+        }
+    }
+
+    /// The score of `allergens`, each counted once. Decodes back to the same
+    /// allergens in either mode.
+    pub fn encode(allergens: impl IntoIterator<Item = Allergen>) -> u32 {
+        allergens.into_iter().collect::<Self>().score()
+    }
+}
+
+#[test]
+fn strict_decoding_reports_unknown_bits() {
+    let error = Allergies::decode(509, Mode::Strict).unwrap_err();
+    assert_eq!(error.unknown(), 256);
+    assert_eq!(error.positions().collect::<Vec<_>>(), [8]);
+    assert_eq!(error.known(), Allergies::new(253));
+    assert_eq!(
+        Allergies::decode(1 << 31 | 1 << 9 | 1, Mode::Strict)
+            .unwrap_err()
+            .to_string(),
+        "score 2147484161 has unknown allergen bits 9, 31"
+    );
+    assert_eq!(
+        Allergies::decode(255, Mode::Strict),
+        Ok(Allergies::new(255))
+    );
+}
+
+#[test]
+fn lenient_decoding_drops_unknown_bits() {
+    let allergies = Allergies::decode(509, Mode::Lenient).unwrap();
+    assert_eq!(allergies.score(), 253);
+    assert_eq!(allergies.allergies(), Allergies::new(509).allergies());
+    assert_eq!(Allergies::decode(256, Mode::Lenient), Ok(Allergies::new(0)));
+}
+
+#[test]
+fn encoding() {
+    assert_eq!(Allergies::encode([]), 0);
+    assert_eq!(
+        Allergies::encode([Allergen::Cats, Allergen::Eggs, Allergen::Cats]),
+        129
+    );
+    assert_eq!(Allergies::encode(Allergen::VALUES), Allergies::KNOWN);
+}
+
+#[test]
+fn known_bits_are_one_per_allergen() {
+    assert_eq!(
+        Allergies::KNOWN.count_ones() as usize,
+        Allergen::VALUES.len()
+    );
+    for allergen in Allergen::VALUES {
+        assert_eq!(allergen.score().count_ones(), 1, "{allergen:?}");
+        assert_eq!(
+            Allergies::decode(allergen.score(), Mode::Strict),
+            Ok(Allergies::new(allergen.score()))
+        );
+    }
+}
+
+#[test]
+fn encoding_round_trips() {
+    use std::collections::HashSet;
+
+    fn prop(indices: Vec<u8>) -> bool {
+        let allergens = indices
+            .iter()
+            .map(|&index| Allergen::VALUES[usize::from(index) % Allergen::VALUES.len()])
+            .collect::<HashSet<_>>();
+        let score = Allergies::encode(allergens.iter().copied());
+        [Mode::Strict, Mode::Lenient].into_iter().all(|mode| {
+            Allergies::decode(score, mode)
+                .is_ok_and(|decoded| decoded.into_iter().collect::<HashSet<_>>() == allergens)
+        })
+    }
+    quickcheck::quickcheck(prop as fn(Vec<u8>) -> bool);
+}
+
+#[test]
+fn decoding_round_trips() {
+    fn prop(score: u32) -> bool {
+        let lenient = Allergies::decode(score, Mode::Lenient).unwrap();
+        let known = Allergies::encode(lenient) == score & Allergies::KNOWN;
+        let strict = match Allergies::decode(score, Mode::Strict) {
+            Ok(strict) => strict == lenient && score == strict.score(),
+            Err(error) => {
+                error.known() == lenient
+                    && error.unknown() != 0
+                    && error.unknown() | lenient.score() == score
+                    && error
+                        .positions()
+                        .all(|bit| bit >= 8 && score >> bit & 1 == 1)
+            }
+        };
+        known && strict
+    }
+    quickcheck::quickcheck(prop as fn(u32) -> bool);
+}
@@ -0,0 +1,428 @@
+pub mod bitset;
+pub mod decode;
+pub mod registry;
+
+use std::fmt;
+use std::str::FromStr;
+
+use bitset::BitSet;
+use registry::{AllergenId, AllergenSet};
+
+/// A patient's allergies, as a score with a bit for each [`Allergen`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Allergies {
+    score: u32,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Allergen {
+    Eggs = 0b00000001,
+    Peanuts = 0b00000010,
+    Shellfish = 0b00000100,
+    Strawberries = 0b00001000,
+    Tomatoes = 0b00010000,
+    Chocolate = 0b00100000,
+    Pollen = 0b01000000,
+    Cats = 0b10000000,
+}
+
+impl Allergen {
+    pub const VALUES: [Self; 8] = [
+        Self::Eggs,
+        Self::Peanuts,
+        Self::Shellfish,
+        Self::Strawberries,
+        Self::Tomatoes,
+        Self::Chocolate,
+        Self::Pollen,
+        Self::Cats,
+    ];
+
+    /// The allergen's bit in a score.
+    pub fn score(self) -> u32 {
+        self as u32
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Eggs => "eggs",
+            Self::Peanuts => "peanuts",
+            Self::Shellfish => "shellfish",
+            Self::Strawberries => "strawberries",
+            Self::Tomatoes => "tomatoes",
+            Self::Chocolate => "chocolate",
+            Self::Pollen => "pollen",
+            Self::Cats => "cats",
+        }
+    }
+}
+
+impl fmt::Display for Allergen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAllergenError(String);
+
+impl fmt::Display for ParseAllergenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown allergen {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseAllergenError {}
+
+/// The name [`Display`](fmt::Display) writes, in any case.
+impl FromStr for Allergen {
+    type Err = ParseAllergenError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::VALUES
+            .into_iter()
+            .find(|allergen| allergen.name().eq_ignore_ascii_case(input))
+            .ok_or_else(|| ParseAllergenError(input.to_owned()))
+    }
+}
+
+/// Its id in the classic [`Registry`](registry::Registry).
+impl From<Allergen> for AllergenId {
+    fn from(allergen: Allergen) -> Self {
+        AllergenId::new(allergen.score().trailing_zeros() as usize)
+    }
+}
+
+impl Allergies {
+    pub fn new(score: u32) -> Self {
+        Allergies { score }
+    }
+
+    /// The score this was made from, bits of unknown allergens included.
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Every bit of the score as an allergen of the classic
+    /// [`Registry`](registry::Registry), bits past the eight included.
+    pub fn allergen_set(&self) -> AllergenSet {
+        AllergenSet::from(BitSet::from_word(self.score.into()))
+    }
+
+    pub fn is_allergic_to(&self, allergen: &Allergen) -> bool {
+        self.score & allergen.score() != 0
+    }
+
+    pub fn allergies(&self) -> Vec<Allergen> {
+        self.iter().collect()
+    }
+
+    /// The known allergens, in the order of [`Allergen::VALUES`].
+    pub fn iter(&self) -> IntoIter {
+        self.into_iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// No known allergens, whatever other bits the score has.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Allergic to either: in another patient's company, say.
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(self.score | other.score)
+    }
+
+    /// Allergies the two patients share.
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self::new(self.score & other.score)
+    }
+
+    /// Allergies of this patient but not of `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        Self::new(self.score & !other.score)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.score & !other.score == 0
+    }
+}
+
+impl From<Allergies> for u32 {
+    fn from(allergies: Allergies) -> Self {
+        allergies.score
+    }
+}
+
+impl FromIterator<Allergen> for Allergies {
+    fn from_iter<I: IntoIterator<Item = Allergen>>(iter: I) -> Self {
+        Self::new(
+            iter.into_iter()
+                .fold(0, |score, allergen| score | allergen.score()),
+        )
+    }
+}
+
+impl Extend<Allergen> for Allergies {
+    fn extend<I: IntoIterator<Item = Allergen>>(&mut self, iter: I) {
+        self.score |= Self::from_iter(iter).score;
+    }
+}
+
+impl IntoIterator for Allergies {
+    type Item = Allergen;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter {
+            allergies: self,
+            next: 0,
+        }
+    }
+}
+
+impl IntoIterator for &Allergies {
+    type Item = Allergen;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        (*self).into_iter()
+    }
+}
+
+/// The known allergens of an [`Allergies`].
+#[derive(Debug, Clone)]
+pub struct IntoIter {
+    allergies: Allergies,
+    /// Index into [`Allergen::VALUES`].
+    next: usize,
+}
+
+impl Iterator for IntoIter {
+    type Item = Allergen;
+
+    fn next(&mut self) -> Option<Allergen> {
+        let values = Allergen::VALUES.get(self.next..)?;
+        let offset = values
+            .iter()
+            .position(|allergen| self.allergies.is_allergic_to(allergen));
+        self.next = offset.map_or(Allergen::VALUES.len(), |offset| self.next + offset + 1);
+        offset.map(|offset| values[offset])
+    }
+}
+
+#[test]
+fn allergen_names_round_trip() {
+    for allergen in Allergen::VALUES {
+        assert_eq!(allergen.to_string().parse(), Ok(allergen));
+    }
+    assert_eq!("Shellfish".parse(), Ok(Allergen::Shellfish));
+    assert_eq!(
+        "gluten".parse::<Allergen>().unwrap_err().to_string(),
+        "unknown allergen \"gluten\""
+    );
+    let registry = registry::Registry::default();
+    for allergen in Allergen::VALUES {
+        let entry = registry.get(allergen.into()).unwrap();
+        assert_eq!(entry.name(), allergen.name());
+    }
+}
+
+#[test]
+fn set_operations_between_patients() {
+    let alice = Allergies::from_iter([Allergen::Eggs, Allergen::Cats, Allergen::Pollen]);
+    let bob = [Allergen::Cats, Allergen::Peanuts]
+        .into_iter()
+        .collect::<Allergies>();
+
+    assert_eq!(alice.score(), 0b1100_0001);
+    assert_eq!(
+        alice.union(&bob).allergies(),
+        [
+            Allergen::Eggs,
+            Allergen::Peanuts,
+            Allergen::Pollen,
+            Allergen::Cats
+        ]
+    );
+    assert_eq!(alice.intersection(&bob).allergies(), [Allergen::Cats]);
+    assert_eq!(
+        alice.difference(&bob).allergies(),
+        [Allergen::Eggs, Allergen::Pollen]
+    );
+    assert!(alice.intersection(&bob).is_subset(&bob));
+    assert!(!alice.is_subset(&bob));
+
+    let mut both = alice;
+    both.extend(bob);
+    assert_eq!(both, alice.union(&bob));
+    assert_eq!(u32::from(both), both.score());
+}
+
+#[test]
+fn iterating_skips_unknown_bits() {
+    let allergies = Allergies::new(256 | 128 | 1);
+    let mut seen = Vec::new();
+    for allergen in &allergies {
+        seen.push(allergen);
+    }
+    assert_eq!(seen, [Allergen::Eggs, Allergen::Cats]);
+    assert_eq!(allergies.len(), 2);
+    assert!(Allergies::new(256).is_empty());
+    assert_eq!(Allergies::new(256).score(), 256);
+    assert_eq!(allergies.into_iter().collect::<Allergies>().score(), 129);
+}
+
+fn compare_allergy_vectors(expected: &[Allergen], actual: &[Allergen]) {
+    for element in expected {
+        if !actual.contains(element) {
+            panic!("Allergen missing\n  {element:?} should be in {actual:?}");
+        }
+    }
+
+    if actual.len() != expected.len() {
+        panic!(
+            "Allergy vectors are of different lengths\n  expected {expected:?}\n  got {actual:?}"
+        );
+    }
+}
+
+#[test]
+fn allergen_set_keeps_unknown_bits() {
+    let registry = registry::Registry::default();
+    let set = Allergies::new(509).allergen_set();
+    assert_eq!(set.len(), 8);
+    assert_eq!(set.entries(&registry).count(), 7);
+    assert!(!set.contains(registry.by_name("peanuts").unwrap()));
+    assert!(set.contains(registry::AllergenId::new(8)));
+}
+
+#[test]
+fn is_not_allergic_to_anything() {
+    let allergies = Allergies::new(0);
+    assert!(!allergies.is_allergic_to(&Allergen::Peanuts));
+    assert!(!allergies.is_allergic_to(&Allergen::Cats));
+    assert!(!allergies.is_allergic_to(&Allergen::Strawberries));
+}
+
+#[test]
+#[ignore]
+fn is_allergic_to_eggs() {
+    assert!(Allergies::new(1).is_allergic_to(&Allergen::Eggs));
+}
+
+#[test]
+#[ignore]
+fn is_allergic_to_eggs_and_shellfish_but_not_strawberries() {
+    let allergies = Allergies::new(5);
+    assert!(allergies.is_allergic_to(&Allergen::Eggs));
+    assert!(allergies.is_allergic_to(&Allergen::Shellfish));
+    assert!(!allergies.is_allergic_to(&Allergen::Strawberries));
+}
+
+#[test]
+#[ignore]
+fn no_allergies_at_all() {
+    let expected = &[];
+    let allergies = Allergies::new(0).allergies();
+
+    compare_allergy_vectors(expected, &allergies);
+}
+
+#[test]
+#[ignore]
+fn allergic_to_just_eggs() {
+    let expected = &[Allergen::Eggs];
+    let allergies = Allergies::new(1).allergies();
+
+    compare_allergy_vectors(expected, &allergies);
+}
+
+#[test]
+#[ignore]
+fn allergic_to_just_peanuts() {
+    let expected = &[Allergen::Peanuts];
+    let allergies = Allergies::new(2).allergies();
+
+    compare_allergy_vectors(expected, &allergies);
+}
+
+#[test]
+#[ignore]
+fn allergic_to_just_strawberries() {
+    let expected = &[Allergen::Strawberries];
+    let allergies = Allergies::new(8).allergies();
+
+    compare_allergy_vectors(expected, &allergies);
+}
+
+#[test]
+#[ignore]
+fn allergic_to_eggs_and_peanuts() {
+    let expected = &[Allergen::Eggs, Allergen::Peanuts];
+    let allergies = Allergies::new(3).allergies();
+
+    compare_allergy_vectors(expected, &allergies);
+}
+
+#[test]
+#[ignore]
+fn allergic_to_eggs_and_shellfish() {
+    let expected = &[Allergen::Eggs, Allergen::Shellfish];
+    let allergies = Allergies::new(5).allergies();
+
+    compare_allergy_vectors(expected, &allergies);
+}
+
+#[test]
+#[ignore]
+fn allergic_to_many_things() {
+    let expected = &[
+        Allergen::Strawberries,
+        Allergen::Tomatoes,
+        Allergen::Chocolate,
+        Allergen::Pollen,
+        Allergen::Cats,
+    ];
+    let allergies = Allergies::new(248).allergies();
+
+    compare_allergy_vectors(expected, &allergies);
+}
+
+#[test]
+#[ignore]
+fn allergic_to_everything() {
+    let expected = &[
+        Allergen::Eggs,
+        Allergen::Peanuts,
+        Allergen::Shellfish,
+        Allergen::Strawberries,
+        Allergen::Tomatoes,
+        Allergen::Chocolate,
+        Allergen::Pollen,
+        Allergen::Cats,
+    ];
+    let allergies = Allergies::new(255).allergies();
+
+    compare_allergy_vectors(expected, &allergies);
+}
+
+#[test]
+#[ignore]
+fn scores_over_255_do_not_trigger_false_positives() {
+    let expected = &[
+        Allergen::Eggs,
+        Allergen::Shellfish,
+        Allergen::Strawberries,
+        Allergen::Tomatoes,
+        Allergen::Chocolate,
+        Allergen::Pollen,
+        Allergen::Cats,
+    ];
+    let allergies = Allergies::new(509).allergies();
+
+    compare_allergy_vectors(expected, &allergies);
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::bitset::BitSet;
+
+/// An allergen in a [`Registry`]: the order it was registered in, and its
+/// bit in an allergy score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AllergenId(usize);
+
+impl AllergenId {
+    pub fn new(index: usize) -> Self {
+        Self(index)
+    }
+
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    name: String,
+    code: String,
+    category: String,
+}
+
+impl Entry {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// A short code for the allergen, unique in its registry.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn category(&self) -> &str {
+        &self.category
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    DuplicateName(String),
+    DuplicateCode(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DuplicateName(name) => write!(f, "allergen {name:?} is already registered"),
+            Self::DuplicateCode(code) => write!(f, "allergen code {code:?} is already taken"),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+/// The allergens a clinic tests for, each with a name, a code and a
+/// category. The default is the classic eight, in the order of their bits
+/// in an [`Allergies`](super::Allergies) score.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registry {
+    entries: Vec<Entry>,
+    names: HashMap<String, AllergenId>,
+    codes: HashMap<String, AllergenId>,
+}
+
+impl Registry {
+    /// A registry without any allergens.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            names: HashMap::new(),
+            codes: HashMap::new(),
+        }
+    }
+
+    /// Add an allergen, with the next free id.
+    pub fn register(
+        &mut self,
+        name: &str,
+        code: &str,
+        category: &str,
+    ) -> Result<AllergenId, RegistryError> {
+        if self.names.contains_key(name) {
+            return Err(RegistryError::DuplicateName(name.to_owned()));
+        }
+        if self.codes.contains_key(code) {
+            return Err(RegistryError::DuplicateCode(code.to_owned()));
+        }
+        let id = AllergenId(self.entries.len());
+        self.names.insert(name.to_owned(), id);
+        self.codes.insert(code.to_owned(), id);
+        self.entries.push(Entry {
+            name: name.to_owned(),
+            code: code.to_owned(),
+            category: category.to_owned(),
+        });
+        Ok(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, id: AllergenId) -> Option<&Entry> {
+        self.entries.get(id.0)
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<AllergenId> {
+        self.names.get(name).copied()
+    }
+
+    pub fn by_code(&self, code: &str) -> Option<AllergenId> {
+        self.codes.get(code).copied()
+    }
+
+    /// Every allergen, in the order they were registered.
+    pub fn iter(&self) -> impl Iterator<Item = (AllergenId, &Entry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (AllergenId(index), entry))
+    }
+
+    /// All of the registry's allergens in `category`.
+    pub fn category(&self, category: &str) -> AllergenSet {
+        self.iter()
+            .filter(|(_, entry)| entry.category == category)
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Every allergen in the registry.
+    pub fn all(&self) -> AllergenSet {
+        self.iter().map(|(id, _)| id).collect()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        for (name, code, category) in [
+            ("eggs", "EGG", "food"),
+            ("peanuts", "PNT", "food"),
+            ("shellfish", "SHF", "food"),
+            ("strawberries", "STR", "food"),
+            ("tomatoes", "TOM", "food"),
+            ("chocolate", "CHO", "food"),
+            ("pollen", "POL", "environmental"),
+            ("cats", "CAT", "animal"),
+        ] {
+            registry
+                .register(name, code, category)
+                .expect("the classic allergens are distinct");
+        }
+        registry
+    }
+}
+
+/// Allergens from a [`Registry`], as many as it has.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AllergenSet(BitSet);
+
+impl AllergenSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `id` was new.
+    pub fn insert(&mut self, id: AllergenId) -> bool {
+        self.0.insert(id.0)
+    }
+
+    /// Whether `id` was there.
+    pub fn remove(&mut self, id: AllergenId) -> bool {
+        self.0.remove(id.0)
+    }
+
+    pub fn contains(&self, id: AllergenId) -> bool {
+        self.0.contains(id.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The allergens, by id.
+    pub fn iter(&self) -> impl Iterator<Item = AllergenId> + '_ {
+        self.0.iter().map(AllergenId)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        set.0.union_with(&other.0);
+        set
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        set.0.intersect_with(&other.0);
+        set
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        set.0.difference_with(&other.0);
+        set
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.0.is_subset(&other.0)
+    }
+
+    /// The registry's entries for the allergens, skipping any it doesn't
+    /// have.
+    pub fn entries<'a>(&'a self, registry: &'a Registry) -> impl Iterator<Item = &'a Entry> {
+        self.iter().filter_map(|id| registry.get(id))
+    }
+
+    /// The bits behind the set, bit `i` for the allergen with id `i`.
+    pub fn bits(&self) -> &BitSet {
+        &self.0
+    }
+}
+
+impl From<BitSet> for AllergenSet {
+    fn from(bits: BitSet) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<AllergenSet> for BitSet {
+    fn from(set: AllergenSet) -> Self {
+        set.0
+    }
+}
+
+impl FromIterator<AllergenId> for AllergenSet {
+    fn from_iter<I: IntoIterator<Item = AllergenId>>(iter: I) -> Self {
+        Self(iter.into_iter().map(|id| id.0).collect())
+    }
+}
+
+impl Extend<AllergenId> for AllergenSet {
+    fn extend<I: IntoIterator<Item = AllergenId>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(|id| id.0));
+    }
+}
+
+#[test]
+fn classic_registry() {
+    let registry = Registry::default();
+    assert_eq!(registry.len(), 8);
+    let cats = registry.by_name("cats").unwrap();
+    assert_eq!(cats.index(), 7);
+    assert_eq!(registry.by_code("CAT"), Some(cats));
+    assert_eq!(registry.get(cats).unwrap().category(), "animal");
+    assert_eq!(registry.category("food").len(), 6);
+    assert_eq!(registry.all().bits().to_word(), Some(0xff));
+}
+
+#[test]
+fn registering_beyond_the_classic_eight() {
+    let mut registry = Registry::default();
+    let codes = (0..100)
+        .map(|n| registry.register(&format!("allergen {n}"), &format!("A{n}"), "drug"))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(codes[99].index(), 107);
+    assert_eq!(
+        registry.register("eggs", "EGG2", "food"),
+        Err(RegistryError::DuplicateName("eggs".to_owned()))
+    );
+    assert_eq!(
+        registry
+            .register("latex", "A3", "other")
+            .unwrap_err()
+            .to_string(),
+        "allergen code \"A3\" is already taken"
+    );
+
+    let mut patient = AllergenSet::new();
+    patient.insert(registry.by_name("peanuts").unwrap());
+    patient.insert(codes[99]);
+    let names = patient
+        .entries(&registry)
+        .map(Entry::name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["peanuts", "allergen 99"]);
+    assert_eq!(patient.intersection(&registry.category("drug")).len(), 1);
+    assert_eq!(patient.difference(&registry.category("drug")).len(), 1);
+    assert!(patient.is_subset(&registry.all()));
+}
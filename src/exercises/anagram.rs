@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-mod anagram {
+pub mod anagram {
     use std::collections::{HashMap, HashSet};
     pub fn anagrams_for<'a>(word: &str, possible_anagrams: &'a [&str]) -> HashSet<&'a str> {
         // let word_hash: HashSet<char> = word.to_lowercase().chars().collect();
@@ -0,0 +1,262 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A classic minefield, eight neighbors and hard edges, packed one bit per
+/// square for boards far too big to annotate square by square.
+///
+/// Counts come a whole row at a time: the neighboring rows are shifted one
+/// column each way and the eight resulting masks summed in bit-sliced
+/// counters, 64 squares per word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitBoard {
+    width: usize,
+    height: usize,
+    /// Words per row; bit `i` of a row's word `w` is column `64 * w + i`.
+    stride: usize,
+    bits: Vec<u64>,
+}
+
+impl BitBoard {
+    pub fn new(width: usize, height: usize) -> Self {
+        let stride = width.div_ceil(BITS);
+        Self {
+            width,
+            height,
+            stride,
+            bits: vec![0; stride * height],
+        }
+    }
+
+    /// As lenient as [`annotate`](super::minesweeper::annotate): any char
+    /// other than `*` is a blank, and short rows are padded with blanks.
+    pub fn from_rows(rows: &[&str]) -> Self {
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut board = Self::new(width, rows.len());
+        for (row, line) in rows.iter().enumerate() {
+            for (col, ch) in line.chars().enumerate() {
+                if ch == '*' {
+                    board.set_mine(row, col, true);
+                }
+            }
+        }
+        board
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_mine(&self, row: usize, col: usize) -> bool {
+        let (word, bit) = self.position(row, col);
+        self.bits[word] >> bit & 1 == 1
+    }
+
+    pub fn set_mine(&mut self, row: usize, col: usize, mine: bool) {
+        let (word, bit) = self.position(row, col);
+        if mine {
+            self.bits[word] |= 1 << bit;
+        } else {
+            self.bits[word] &= !(1 << bit);
+        }
+    }
+
+    pub fn mine_count(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Neighboring mines of every square, row by row, mines included.
+    pub fn counts(&self) -> Vec<u8> {
+        let mut counts = vec![0; self.width * self.height];
+        if self.width > 0 {
+            for (row, out) in counts.chunks_mut(self.width).enumerate() {
+                self.count_row(row, out);
+            }
+        }
+        counts
+    }
+
+    /// [`counts`](Self::counts), a row per task on the current rayon pool.
+    #[cfg(feature = "rayon")]
+    pub fn par_counts(&self) -> Vec<u8> {
+        let mut counts = vec![0; self.width * self.height];
+        if self.width > 0 {
+            counts
+                .par_chunks_mut(self.width)
+                .enumerate()
+                .for_each(|(row, out)| self.count_row(row, out));
+        }
+        counts
+    }
+
+    /// Rows the way [`Board::annotate`](super::board::Board::annotate) has
+    /// them for a classic board.
+    pub fn annotate(&self) -> Vec<String> {
+        self.render(self.counts())
+    }
+
+    #[cfg(feature = "rayon")]
+    pub fn par_annotate(&self) -> Vec<String> {
+        self.render(self.par_counts())
+    }
+
+    fn render(&self, counts: Vec<u8>) -> Vec<String> {
+        (0..self.height)
+            .map(|row| {
+                let bytes = (0..self.width)
+                    .map(|col| match counts[row * self.width + col] {
+                        _ if self.is_mine(row, col) => b'*',
+                        0 => b' ',
+                        count => b'0' + count,
+                    })
+                    .collect();
+                String::from_utf8(bytes).expect("annotations are ASCII")
+            })
+            .collect()
+    }
+
+    fn count_row(&self, row: usize, out: &mut [u8]) {
+        let empty = vec![0; self.stride];
+        let line = |row: Option<usize>| match row {
+            Some(row) if row < self.height => &self.bits[row * self.stride..][..self.stride],
+            _ => &empty[..],
+        };
+        let (above, here, below) = (
+            line(row.checked_sub(1)),
+            line(Some(row)),
+            line(row.checked_add(1)),
+        );
+
+        for word in 0..self.stride {
+            // Four bit planes: bit i of planes[p] is bit p of column i's count.
+            let mut planes = [0u64; 4];
+            let mut add = |mask: u64| {
+                let mut carry = mask;
+                for plane in &mut planes {
+                    let next = *plane & carry;
+                    *plane ^= carry;
+                    carry = next;
+                }
+            };
+            for (line, middle) in [(above, true), (here, false), (below, true)] {
+                let previous = if word > 0 { line[word - 1] } else { 0 };
+                let next = line.get(word + 1).copied().unwrap_or(0);
+                // The mine one column left of each square, then one right.
+                add(line[word] << 1 | previous >> (BITS - 1));
+                add(line[word] >> 1 | next << (BITS - 1));
+                if middle {
+                    add(line[word]);
+                }
+            }
+
+            let start = word * BITS;
+            for (bit, count) in out[start..(start + BITS).min(self.width)]
+                .iter_mut()
+                .enumerate()
+            {
+                *count = planes
+                    .iter()
+                    .enumerate()
+                    .map(|(p, plane)| ((plane >> bit & 1) as u8) << p)
+                    .sum();
+            }
+        }
+    }
+
+    fn position(&self, row: usize, col: usize) -> (usize, usize) {
+        assert!(
+            row < self.height && col < self.width,
+            "({row}, {col}) is off the board"
+        );
+        (row * self.stride + col / BITS, col % BITS)
+    }
+}
+
+/// [`annotate`](super::minesweeper::annotate) by way of a [`BitBoard`].
+pub fn annotate(minefield: &[&str]) -> Vec<String> {
+    truncate(BitBoard::from_rows(minefield).annotate(), minefield)
+}
+
+#[cfg(feature = "rayon")]
+pub fn par_annotate(minefield: &[&str]) -> Vec<String> {
+    truncate(BitBoard::from_rows(minefield).par_annotate(), minefield)
+}
+
+/// Ragged rows keep their own length.
+fn truncate(annotated: Vec<String>, minefield: &[&str]) -> Vec<String> {
+    annotated
+        .into_iter()
+        .zip(minefield)
+        .map(|(mut annotated, row)| {
+            annotated.truncate(row.chars().count());
+            annotated
+        })
+        .collect()
+}
+
+#[test]
+fn agrees_with_the_board_across_word_boundaries() {
+    use super::board::Board;
+    use super::rng::Rng;
+
+    let mut rng = Rng::new(5);
+    for (width, height) in [(1, 1), (63, 3), (64, 4), (65, 5), (130, 7), (200, 2)] {
+        let rows = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| if rng.below(3) == 0 { '*' } else { ' ' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        let rows = rows.iter().map(String::as_str).collect::<Vec<_>>();
+        let bits = BitBoard::from_rows(&rows);
+        assert_eq!(bits.annotate(), Board::from_rows(&rows).annotate());
+        assert_eq!(bits.mine_count(), Board::from_rows(&rows).mine_count());
+    }
+}
+
+#[test]
+fn eight_neighbors_and_ragged_rows() {
+    let rows = ["***", "* *", "***"];
+    assert_eq!(annotate(&rows), ["***", "*8*", "***"]);
+    assert_eq!(BitBoard::from_rows(&rows).counts()[4], 8);
+    let rows = ["*  ", " ", "", "  *"];
+    assert_eq!(annotate(&rows), super::minesweeper::annotate(&rows));
+    assert_eq!(annotate(&[]), Vec::<String>::new());
+    assert_eq!(annotate(&["", ""]), ["", ""]);
+}
+
+#[test]
+fn set_and_clear() {
+    let mut board = BitBoard::new(100, 2);
+    board.set_mine(1, 99, true);
+    board.set_mine(0, 64, true);
+    assert!(board.is_mine(1, 99) && board.is_mine(0, 64));
+    board.set_mine(0, 64, false);
+    assert_eq!(board.mine_count(), 1);
+    assert_eq!(board.counts()[98], 1);
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn parallel_matches_sequential() {
+    let mut board = BitBoard::new(300, 300);
+    let mut rng = super::rng::Rng::new(9);
+    for _ in 0..10_000 {
+        board.set_mine(rng.below(300), rng.below(300), true);
+    }
+    assert_eq!(board.par_counts(), board.counts());
+    assert_eq!(board.par_annotate(), board.annotate());
+}
@@ -0,0 +1,420 @@
+use std::fmt;
+
+use super::field::{self, Minefield};
+
+/// Which squares count as neighbors of a square, as `(row, col)` offsets.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Neighborhood {
+    /// The eight surrounding squares, as in classic Minesweeper.
+    #[default]
+    Moore,
+    /// The four orthogonally adjacent squares.
+    VonNeumann,
+    /// The eight squares a chess knight can jump to.
+    Knight,
+    /// The six sides of a hexagon, in axial coordinates: `col` is `q` and
+    /// `row` is `r`, so each row sits half a square further right than the
+    /// one above and the board is a rhombus.
+    Hex,
+    /// Any offsets. `(0, 0)` and repeated offsets are ignored.
+    Custom(Vec<(isize, isize)>),
+}
+
+impl Neighborhood {
+    pub fn offsets(&self) -> &[(isize, isize)] {
+        match self {
+            Self::Moore => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+            Self::VonNeumann => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Self::Knight => &[
+                (-2, -1),
+                (-2, 1),
+                (-1, -2),
+                (-1, 2),
+                (1, -2),
+                (1, 2),
+                (2, -1),
+                (2, 1),
+            ],
+            Self::Hex => &[(-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0)],
+            Self::Custom(offsets) => offsets,
+        }
+    }
+}
+
+/// What happens to neighbors that fall off the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// They don't exist.
+    #[default]
+    Bounded,
+    /// They wrap around to the opposite edge, in both directions.
+    Torus,
+}
+
+/// Where and why [`Board::parse`] rejected its input. Columns count chars,
+/// not bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub row: usize,
+    pub col: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The row is longer or shorter than the first one; `col` is where one
+    /// of them ends.
+    Ragged { expected: usize, found: usize },
+    /// Neither `*` nor a blank.
+    InvalidSquare(char),
+    /// A layer of a [`Cube`](super::cube::Cube) with more or fewer rows than
+    /// the first one; `row` is where one of them ends.
+    Rows { expected: usize, found: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "row {}, column {}: ", self.row, self.col)?;
+        match self.kind {
+            ParseErrorKind::Ragged { expected, found } => {
+                write!(f, "expected {expected} squares, found {found}")
+            }
+            ParseErrorKind::InvalidSquare(square) => {
+                write!(f, "{square:?} is neither a mine nor a blank")
+            }
+            ParseErrorKind::Rows { expected, found } => {
+                write!(f, "expected {expected} rows, found {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A rectangular minefield, with the rules for which squares are neighbors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    mines: Vec<bool>,
+    neighborhood: Neighborhood,
+    topology: Topology,
+}
+
+impl Board {
+    /// An empty `width` by `height` board with classic neighbors.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            mines: vec![false; width * height],
+            neighborhood: Neighborhood::default(),
+            topology: Topology::default(),
+        }
+    }
+
+    /// A board from rows of `*` for mines and blanks for the other squares,
+    /// all of the same length.
+    pub fn parse(rows: &[&str]) -> Result<Self, ParseError> {
+        let width = rows.first().map_or(0, |line| line.chars().count());
+        for (row, line) in rows.iter().enumerate() {
+            let mut len = 0;
+            for (col, square) in line.chars().enumerate() {
+                if col == width {
+                    let found = line.chars().count();
+                    let kind = ParseErrorKind::Ragged {
+                        expected: width,
+                        found,
+                    };
+                    return Err(ParseError { row, col, kind });
+                }
+                if square != '*' && square != ' ' {
+                    let kind = ParseErrorKind::InvalidSquare(square);
+                    return Err(ParseError { row, col, kind });
+                }
+                len += 1;
+            }
+            if len < width {
+                let kind = ParseErrorKind::Ragged {
+                    expected: width,
+                    found: len,
+                };
+                return Err(ParseError {
+                    row,
+                    col: len,
+                    kind,
+                });
+            }
+        }
+        Ok(Self::from_rows(rows))
+    }
+
+    /// A board from rows where `*` is a mine and any other char is not. Rows
+    /// shorter than the longest are padded with squares without mines.
+    pub fn from_rows(rows: &[&str]) -> Self {
+        let width = rows
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut board = Self::new(width, rows.len());
+        for (row, line) in rows.iter().enumerate() {
+            for (col, square) in line.chars().enumerate() {
+                board.set_mine(row, col, square == '*');
+            }
+        }
+        board
+    }
+
+    pub fn with_neighborhood(mut self, neighborhood: Neighborhood) -> Self {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn neighborhood(&self) -> &Neighborhood {
+        &self.neighborhood
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn is_mine(&self, row: usize, col: usize) -> bool {
+        self.mines[self.index(row, col)]
+    }
+
+    pub fn set_mine(&mut self, row: usize, col: usize, mine: bool) {
+        let index = self.index(row, col);
+        self.mines[index] = mine;
+    }
+
+    pub fn mine_count(&self) -> usize {
+        self.mines.iter().filter(|&&mine| mine).count()
+    }
+
+    /// The distinct squares around `(row, col)`, in offset order. On a small
+    /// torus several offsets can wrap onto the same square, or back onto
+    /// `(row, col)` itself; each square is listed once and never its own
+    /// neighbor.
+    pub fn neighbors(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let steps = self.neighborhood.offsets().iter();
+        field::distinct(
+            (row, col),
+            steps.map(|&(dr, dc)| self.step(row, col, dr, dc)),
+        )
+    }
+
+    /// Mines among the neighbors of `(row, col)`.
+    pub fn count(&self, row: usize, col: usize) -> usize {
+        Minefield::count(self, (row, col))
+    }
+
+    /// Rows with `*` for mines, a blank for squares without neighboring
+    /// mines and the count for the rest. Counts over 9, only possible with a
+    /// custom neighborhood, go on with `a`, `b` and so on.
+    pub fn annotate(&self) -> Vec<String> {
+        (0..self.height)
+            .map(|row| (0..self.width).map(|col| self.symbol((row, col))).collect())
+            .collect()
+    }
+
+    fn index(&self, row: usize, col: usize) -> usize {
+        assert!(
+            row < self.height && col < self.width,
+            "({row}, {col}) is off the board"
+        );
+        row * self.width + col
+    }
+
+    fn step(&self, row: usize, col: usize, dr: isize, dc: isize) -> Option<(usize, usize)> {
+        match self.topology {
+            Topology::Bounded => {
+                let row = row
+                    .checked_add_signed(dr)
+                    .filter(|&row| row < self.height)?;
+                let col = col.checked_add_signed(dc).filter(|&col| col < self.width)?;
+                Some((row, col))
+            }
+            Topology::Torus => Some((wrap(row, dr, self.height), wrap(col, dc, self.width))),
+        }
+    }
+}
+
+impl Minefield for Board {
+    type Square = (usize, usize);
+
+    fn is_mine(&self, (row, col): (usize, usize)) -> bool {
+        self.is_mine(row, col)
+    }
+
+    fn neighbors(&self, (row, col): (usize, usize)) -> Vec<(usize, usize)> {
+        self.neighbors(row, col)
+    }
+}
+
+pub(crate) fn wrap(position: usize, offset: isize, len: usize) -> usize {
+    (position as isize + offset).rem_euclid(len as isize) as usize
+}
+
+#[test]
+fn classic_board_matches_annotate() {
+    let expected = ["1*22*1", "12*322", " 123*2", "112*4*", "1*22*2", "111111"];
+    let rows = expected.map(|row| row.replace(|square| square != '*', " "));
+    let rows = rows.iter().map(String::as_str).collect::<Vec<_>>();
+    assert_eq!(Board::from_rows(&rows).annotate(), expected);
+    assert_eq!(super::minesweeper::annotate(&rows), expected);
+}
+
+#[test]
+fn von_neumann_neighbors() {
+    let board =
+        Board::from_rows(&["* *", "   ", "* *"]).with_neighborhood(Neighborhood::VonNeumann);
+    assert_eq!(board.annotate(), ["*2*", "2 2", "*2*"]);
+
+    let board = Board::from_rows(&[" * ", "*  "]).with_neighborhood(Neighborhood::VonNeumann);
+    assert_eq!(board.annotate(), ["2*1", "*2 "]);
+}
+
+#[test]
+fn knight_neighbors() {
+    let board = Board::from_rows(&["*  ", "   ", "   "]).with_neighborhood(Neighborhood::Knight);
+    assert_eq!(board.annotate(), ["*  ", "  1", " 1 "]);
+}
+
+#[test]
+fn custom_neighbors_ignore_self_and_repeats() {
+    let board = Board::from_rows(&["* * * "]).with_neighborhood(Neighborhood::Custom(vec![
+        (0, 0),
+        (0, -1),
+        (0, -1),
+        (0, -3),
+    ]));
+    assert_eq!(board.annotate(), ["*1*2*2"]);
+    assert_eq!(board.neighbors(0, 5), [(0, 4), (0, 2)]);
+}
+
+#[test]
+fn hex_neighbors() {
+    let board = Board::from_rows(&["   ", " * ", "   "]).with_neighborhood(Neighborhood::Hex);
+    // Axial neighbors of (1, 1); the corners (0, 0) and (2, 2) are not.
+    assert_eq!(board.annotate(), [" 11", "1*1", "11 "]);
+    assert_eq!(board.neighbors(0, 0), [(0, 1), (1, 0)]);
+}
+
+#[test]
+fn torus_wraps_both_ways() {
+    let board = Board::from_rows(&["*   ", "    ", "    ", "    "]).with_topology(Topology::Torus);
+    assert_eq!(board.annotate(), ["*1 1", "11 1", "    ", "11 1"]);
+}
+
+#[test]
+fn small_torus_counts_each_square_once() {
+    let board = Board::from_rows(&["* "]).with_topology(Topology::Torus);
+    assert_eq!(board.neighbors(0, 1), [(0, 0)]);
+    assert_eq!(board.annotate(), ["*1"]);
+    assert_eq!(
+        Board::from_rows(&["*"])
+            .with_topology(Topology::Torus)
+            .annotate(),
+        ["*"]
+    );
+}
+
+#[test]
+fn empty_boards() {
+    assert!(Board::from_rows(&[]).annotate().is_empty());
+    assert_eq!(Board::from_rows(&["", ""]).annotate(), ["", ""]);
+    assert_eq!(Board::new(3, 2).mine_count(), 0);
+}
+
+#[test]
+fn parse_accepts_mines_and_blanks() {
+    let board = Board::parse(&["* ", " *"]).unwrap();
+    assert_eq!(board, Board::from_rows(&["* ", " *"]));
+    assert_eq!(Board::parse(&[]), Ok(Board::new(0, 0)));
+}
+
+#[test]
+fn parse_rejects_ragged_rows() {
+    let error = Board::parse(&["  ", "   "]).unwrap_err();
+    let kind = ParseErrorKind::Ragged {
+        expected: 2,
+        found: 3,
+    };
+    assert_eq!(
+        error,
+        ParseError {
+            row: 1,
+            col: 2,
+            kind
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "row 1, column 2: expected 2 squares, found 3"
+    );
+
+    let error = Board::parse(&["  ", "* ", "*"]).unwrap_err();
+    let kind = ParseErrorKind::Ragged {
+        expected: 2,
+        found: 1,
+    };
+    assert_eq!(
+        error,
+        ParseError {
+            row: 2,
+            col: 1,
+            kind
+        }
+    );
+}
+
+#[test]
+fn parse_rejects_other_squares_by_char_column() {
+    let error = Board::parse(&["ü*", "* "]).unwrap_err();
+    let kind = ParseErrorKind::InvalidSquare('ü');
+    assert_eq!(
+        error,
+        ParseError {
+            row: 0,
+            col: 0,
+            kind
+        }
+    );
+
+    let error = Board::parse(&["  ", "*1"]).unwrap_err();
+    assert_eq!(error.col, 1);
+    assert_eq!(
+        error.to_string(),
+        "row 1, column 1: '1' is neither a mine nor a blank"
+    );
+}
+
+#[test]
+fn from_rows_pads_short_rows() {
+    let board = Board::from_rows(&["*", "  ", "é"]);
+    assert_eq!((board.width(), board.height()), (2, 3));
+    assert_eq!(board.annotate(), ["*1", "11", "  "]);
+}
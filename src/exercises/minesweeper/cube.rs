@@ -0,0 +1,297 @@
+use std::fmt;
+
+use super::board::{wrap, Board, ParseError, ParseErrorKind, Topology};
+use super::field::{self, Minefield};
+
+/// Where [`Cube::parse`] rejected a layer, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerError {
+    pub layer: usize,
+    pub error: ParseError,
+}
+
+impl fmt::Display for LayerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "layer {}, {}", self.layer, self.error)
+    }
+}
+
+impl std::error::Error for LayerError {}
+
+/// A minefield of stacked layers, where every square has the 26 neighbors
+/// of a cube in a 3 by 3 by 3 block. Squares are `(layer, row, col)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cube {
+    width: usize,
+    height: usize,
+    depth: usize,
+    mines: Vec<bool>,
+    topology: Topology,
+}
+
+impl Cube {
+    /// An empty cube of `depth` layers of `width` by `height`.
+    pub fn new(width: usize, height: usize, depth: usize) -> Self {
+        Self {
+            width,
+            height,
+            depth,
+            mines: vec![false; width * height * depth],
+            topology: Topology::default(),
+        }
+    }
+
+    /// Layers as [`Board::parse`] reads them, all of the same size.
+    pub fn parse(layers: &[&[&str]]) -> Result<Self, LayerError> {
+        let mut boards = Vec::with_capacity(layers.len());
+        for (layer, rows) in layers.iter().enumerate() {
+            let board = Board::parse(rows).map_err(|error| LayerError { layer, error })?;
+            if let Some(first) = boards.first() {
+                check_size(first, &board).map_err(|error| LayerError { layer, error })?;
+            }
+            boards.push(board);
+        }
+        Ok(Self::from_boards(&boards))
+    }
+
+    /// Layers as [`Board::from_rows`] reads them. Layers smaller than the
+    /// largest are padded with squares without mines.
+    pub fn from_layers(layers: &[&[&str]]) -> Self {
+        let boards = layers
+            .iter()
+            .map(|rows| Board::from_rows(rows))
+            .collect::<Vec<_>>();
+        Self::from_boards(&boards)
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn is_mine(&self, layer: usize, row: usize, col: usize) -> bool {
+        self.mines[self.index(layer, row, col)]
+    }
+
+    pub fn set_mine(&mut self, layer: usize, row: usize, col: usize, mine: bool) {
+        let index = self.index(layer, row, col);
+        self.mines[index] = mine;
+    }
+
+    pub fn mine_count(&self) -> usize {
+        self.mines.iter().filter(|&&mine| mine).count()
+    }
+
+    /// The distinct squares around `(layer, row, col)`, each listed once.
+    pub fn neighbors(&self, layer: usize, row: usize, col: usize) -> Vec<(usize, usize, usize)> {
+        let mut steps = Vec::with_capacity(27);
+        for dl in -1..=1 {
+            for dr in -1..=1 {
+                for dc in -1..=1 {
+                    steps.push(self.step((layer, row, col), (dl, dr, dc)));
+                }
+            }
+        }
+        field::distinct((layer, row, col), steps)
+    }
+
+    /// Mines among the neighbors of `(layer, row, col)`.
+    pub fn count(&self, layer: usize, row: usize, col: usize) -> usize {
+        Minefield::count(self, (layer, row, col))
+    }
+
+    /// Every layer annotated the way [`Board::annotate`] does it, but
+    /// counting neighbors in the layers above and below as well.
+    pub fn annotate(&self) -> Vec<Vec<String>> {
+        (0..self.depth)
+            .map(|layer| {
+                (0..self.height)
+                    .map(|row| {
+                        (0..self.width)
+                            .map(|col| self.symbol((layer, row, col)))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// One layer, for the renderers.
+    ///
+    /// # Panics
+    ///
+    /// If there's no such layer.
+    pub fn layer(&self, layer: usize) -> Layer<'_> {
+        assert!(layer < self.depth, "layer {layer} is out of the cube");
+        Layer { cube: self, layer }
+    }
+
+    fn from_boards(boards: &[Board]) -> Self {
+        let width = boards.iter().map(Board::width).max().unwrap_or(0);
+        let height = boards.iter().map(Board::height).max().unwrap_or(0);
+        let mut cube = Self::new(width, height, boards.len());
+        for (layer, board) in boards.iter().enumerate() {
+            for row in 0..board.height() {
+                for col in 0..board.width() {
+                    cube.set_mine(layer, row, col, board.is_mine(row, col));
+                }
+            }
+        }
+        cube
+    }
+
+    fn index(&self, layer: usize, row: usize, col: usize) -> usize {
+        assert!(
+            layer < self.depth && row < self.height && col < self.width,
+            "({layer}, {row}, {col}) is off the cube"
+        );
+        (layer * self.height + row) * self.width + col
+    }
+
+    fn step(
+        &self,
+        (layer, row, col): (usize, usize, usize),
+        (dl, dr, dc): (isize, isize, isize),
+    ) -> Option<(usize, usize, usize)> {
+        match self.topology {
+            Topology::Bounded => {
+                let layer = layer
+                    .checked_add_signed(dl)
+                    .filter(|&layer| layer < self.depth)?;
+                let row = row
+                    .checked_add_signed(dr)
+                    .filter(|&row| row < self.height)?;
+                let col = col.checked_add_signed(dc).filter(|&col| col < self.width)?;
+                Some((layer, row, col))
+            }
+            Topology::Torus => Some((
+                wrap(layer, dl, self.depth),
+                wrap(row, dr, self.height),
+                wrap(col, dc, self.width),
+            )),
+        }
+    }
+}
+
+impl Minefield for Cube {
+    type Square = (usize, usize, usize);
+
+    fn is_mine(&self, (layer, row, col): (usize, usize, usize)) -> bool {
+        self.is_mine(layer, row, col)
+    }
+
+    fn neighbors(&self, (layer, row, col): (usize, usize, usize)) -> Vec<(usize, usize, usize)> {
+        self.neighbors(layer, row, col)
+    }
+}
+
+/// A single layer of a [`Cube`], counted with its neighbors above and below.
+#[derive(Debug, Clone, Copy)]
+pub struct Layer<'a> {
+    cube: &'a Cube,
+    layer: usize,
+}
+
+impl Layer<'_> {
+    pub fn cube(&self) -> &Cube {
+        self.cube
+    }
+
+    pub fn index(&self) -> usize {
+        self.layer
+    }
+}
+
+/// Layers that don't match the first one in size, checked rows first.
+fn check_size(first: &Board, board: &Board) -> Result<(), ParseError> {
+    if board.height() != first.height() {
+        let kind = ParseErrorKind::Rows {
+            expected: first.height(),
+            found: board.height(),
+        };
+        let row = board.height().min(first.height());
+        return Err(ParseError { row, col: 0, kind });
+    }
+    if board.width() != first.width() {
+        let kind = ParseErrorKind::Ragged {
+            expected: first.width(),
+            found: board.width(),
+        };
+        let col = board.width().min(first.width());
+        return Err(ParseError { row: 0, col, kind });
+    }
+    Ok(())
+}
+
+#[test]
+fn twenty_six_neighbors() {
+    let full = ["***", "***", "***"];
+    let middle = ["***", "* *", "***"];
+    let cube = Cube::from_layers(&[&full, &middle, &full]);
+    assert_eq!(cube.neighbors(1, 1, 1).len(), 26);
+    assert_eq!(cube.count(1, 1, 1), 26);
+    assert_eq!(cube.annotate()[1][1], "*q*");
+    // A corner touches seven others.
+    assert_eq!(Cube::new(3, 3, 3).neighbors(0, 0, 0).len(), 7);
+}
+
+#[test]
+fn counts_reach_across_layers() {
+    let cube = Cube::parse(&[&["*  ", "   "], &["   ", "   "], &["   ", "  *"]]).unwrap();
+    assert_eq!(
+        cube.annotate(),
+        [["*1 ", "11 "], ["121", "121"], [" 11", " 1*"]]
+    );
+    // A single layer is a classic board.
+    let rows = ["*  ", "  *", " * "];
+    let cube = Cube::parse(&[&rows]).unwrap();
+    assert_eq!(cube.annotate()[0], Board::parse(&rows).unwrap().annotate());
+}
+
+#[test]
+fn torus_cube_wraps_between_first_and_last_layers() {
+    let cube = Cube::from_layers(&[&["*   "], &["    "], &["    "], &["    "]])
+        .with_topology(Topology::Torus);
+    assert_eq!(cube.count(3, 0, 0), 1);
+    assert_eq!(cube.count(2, 0, 0), 0);
+    // Three wide and no deeper than one: each square only once.
+    let cube = Cube::from_layers(&[&["*  "]]).with_topology(Topology::Torus);
+    assert_eq!(cube.neighbors(0, 0, 1).len(), 2);
+}
+
+#[test]
+fn layers_must_match() {
+    let error = Cube::parse(&[&["* ", "  "], &["  "]]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "layer 1, row 1, column 0: expected 2 rows, found 1"
+    );
+    let error = Cube::parse(&[&["* ", "  "], &["   ", "   "]]).unwrap_err();
+    assert_eq!(error.layer, 1);
+    assert_eq!(
+        error.error.kind,
+        ParseErrorKind::Ragged {
+            expected: 2,
+            found: 3
+        }
+    );
+    let error = Cube::parse(&[&[" "], &["x"]]).unwrap_err();
+    assert_eq!(error.error.kind, ParseErrorKind::InvalidSquare('x'));
+    assert_eq!(Cube::from_layers(&[&["*"], &["  ", " "]]).width(), 2);
+}
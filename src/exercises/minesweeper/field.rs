@@ -0,0 +1,63 @@
+/// What every shape of minefield has in common: squares that may hold a
+/// mine, and a rule for which squares are next to which. Counting and
+/// annotating are the same whatever the shape.
+pub trait Minefield {
+    /// Coordinates of a square, such as `(row, col)`.
+    type Square: Copy + PartialEq;
+
+    fn is_mine(&self, square: Self::Square) -> bool;
+
+    /// The distinct squares next to `square`, never `square` itself.
+    fn neighbors(&self, square: Self::Square) -> Vec<Self::Square>;
+
+    /// Mines among the neighbors of `square`.
+    fn count(&self, square: Self::Square) -> usize {
+        self.neighbors(square)
+            .into_iter()
+            .filter(|&neighbor| self.is_mine(neighbor))
+            .count()
+    }
+
+    /// `*` for a mine, otherwise what [`count_symbol`] makes of its count.
+    fn symbol(&self, square: Self::Square) -> char {
+        if self.is_mine(square) {
+            '*'
+        } else {
+            count_symbol(self.count(square))
+        }
+    }
+}
+
+/// A blank for no neighboring mines, otherwise the count. Counts over 9 go
+/// on with `a`, `b` and so on, and anything past `z` is a `+`.
+pub fn count_symbol(count: usize) -> char {
+    match count {
+        0 => ' ',
+        count => char::from_digit(count as u32, 36).unwrap_or('+'),
+    }
+}
+
+/// The squares `steps` lands on from `square`, in order, skipping ones off
+/// the field, `square` itself and repeats. Repeats and `square` only turn up
+/// when offsets wrap around a small field.
+pub(crate) fn distinct<S: Copy + PartialEq>(
+    square: S,
+    steps: impl IntoIterator<Item = Option<S>>,
+) -> Vec<S> {
+    let mut neighbors = Vec::new();
+    for neighbor in steps.into_iter().flatten() {
+        if neighbor != square && !neighbors.contains(&neighbor) {
+            neighbors.push(neighbor);
+        }
+    }
+    neighbors
+}
+
+#[test]
+fn count_symbols() {
+    assert_eq!(count_symbol(0), ' ');
+    assert_eq!(count_symbol(8), '8');
+    assert_eq!(count_symbol(10), 'a');
+    assert_eq!(count_symbol(26), 'q');
+    assert_eq!(count_symbol(36), '+');
+}
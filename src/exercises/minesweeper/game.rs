@@ -0,0 +1,402 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use super::board::{Board, Neighborhood, Topology};
+use super::rng::Rng;
+
+/// What the player sees of a square.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Square {
+    Hidden,
+    Flagged,
+    /// A safe square, with the number of neighboring mines.
+    Revealed(usize),
+    /// The mine that ended the game.
+    Exploded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    Reveal(usize, usize),
+    /// Flag a hidden square, or take the flag off again.
+    Flag(usize, usize),
+    /// On a revealed square with as many flags around it as its count,
+    /// reveal all its other neighbors at once.
+    Chord(usize, usize),
+}
+
+impl Move {
+    pub fn square(&self) -> (usize, usize) {
+        match *self {
+            Self::Reveal(row, col) | Self::Flag(row, col) | Self::Chord(row, col) => (row, col),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    OffBoard,
+    GameOver,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OffBoard => write!(f, "square is off the board"),
+            Self::GameOver => write!(f, "game is already over"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// A game of Minesweeper in progress.
+///
+/// Mines of a generated game are only laid on the first reveal, away from
+/// the revealed square and its neighbors where the board leaves room, so the
+/// first click never loses. The same seed and the same first reveal always
+/// give the same board.
+#[derive(Debug, Clone)]
+pub struct Game {
+    board: Board,
+    squares: Vec<Square>,
+    mines: usize,
+    /// Not yet laid, while `Some`.
+    rng: Option<Rng>,
+    status: Status,
+    revealed: usize,
+    flags: usize,
+    history: Vec<Move>,
+}
+
+impl Game {
+    /// A `width` by `height` game with `mines` mines, laid out by `seed`.
+    ///
+    /// # Panics
+    ///
+    /// If there wouldn't be a single safe square.
+    pub fn new(width: usize, height: usize, mines: usize, seed: u64) -> Self {
+        assert!(
+            has_safe_square(width, height, mines),
+            "{mines} mines leave no safe square on a {width} by {height} board"
+        );
+        Self {
+            rng: Some(Rng::new(seed)),
+            mines,
+            ..Self::from_board(Board::new(width, height))
+        }
+    }
+
+    /// A game on a board whose mines are already laid. There is no first
+    /// click safety.
+    pub fn from_board(board: Board) -> Self {
+        Self {
+            squares: vec![Square::Hidden; board.width() * board.height()],
+            mines: board.mine_count(),
+            rng: None,
+            status: Status::Playing,
+            revealed: 0,
+            flags: 0,
+            history: Vec::new(),
+            board,
+        }
+    }
+
+    /// Only has an effect before the first move.
+    pub fn with_neighborhood(mut self, neighborhood: Neighborhood) -> Self {
+        if self.history.is_empty() {
+            self.board = self.board.with_neighborhood(neighborhood);
+        }
+        self
+    }
+
+    /// Only has an effect before the first move.
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        if self.history.is_empty() {
+            self.board = self.board.with_topology(topology);
+        }
+        self
+    }
+
+    /// The minefield. Empty for a generated game until the first reveal.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn width(&self) -> usize {
+        self.board.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.board.height()
+    }
+
+    pub fn mine_count(&self) -> usize {
+        self.mines
+    }
+
+    /// Mines minus flags; negative when the player has flagged too much.
+    pub fn mines_left(&self) -> isize {
+        self.mines as isize - self.flags as isize
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn square(&self, row: usize, col: usize) -> Square {
+        self.squares[self.index(row, col)]
+    }
+
+    /// Every move that was played, in order, including ones that changed
+    /// nothing such as revealing a revealed square.
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    pub fn reveal(&mut self, row: usize, col: usize) -> Result<Status, MoveError> {
+        self.play(Move::Reveal(row, col))
+    }
+
+    pub fn flag(&mut self, row: usize, col: usize) -> Result<Status, MoveError> {
+        self.play(Move::Flag(row, col))
+    }
+
+    pub fn chord(&mut self, row: usize, col: usize) -> Result<Status, MoveError> {
+        self.play(Move::Chord(row, col))
+    }
+
+    pub fn play(&mut self, mv: Move) -> Result<Status, MoveError> {
+        if self.status != Status::Playing {
+            return Err(MoveError::GameOver);
+        }
+        let (row, col) = mv.square();
+        if row >= self.height() || col >= self.width() {
+            return Err(MoveError::OffBoard);
+        }
+        self.history.push(mv);
+        match mv {
+            Move::Reveal(row, col) => {
+                if let Some(rng) = self.rng.take() {
+                    self.lay_mines(rng, row, col);
+                }
+                self.open(row, col);
+            }
+            Move::Flag(row, col) => {
+                let index = self.index(row, col);
+                match self.squares[index] {
+                    Square::Hidden => {
+                        self.squares[index] = Square::Flagged;
+                        self.flags += 1;
+                    }
+                    Square::Flagged => {
+                        self.squares[index] = Square::Hidden;
+                        self.flags -= 1;
+                    }
+                    _ => {}
+                }
+            }
+            Move::Chord(row, col) => {
+                if let Square::Revealed(count) = self.square(row, col) {
+                    let neighbors = self.board.neighbors(row, col);
+                    let flags = neighbors
+                        .iter()
+                        .filter(|&&(row, col)| self.square(row, col) == Square::Flagged)
+                        .count();
+                    if flags == count {
+                        for (row, col) in neighbors {
+                            self.open(row, col);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(self.status)
+    }
+
+    /// Reveal a hidden square, and every square around it while they have
+    /// no neighboring mines.
+    fn open(&mut self, row: usize, col: usize) {
+        if self.status != Status::Playing || self.square(row, col) != Square::Hidden {
+            return;
+        }
+        if self.board.is_mine(row, col) {
+            let index = self.index(row, col);
+            self.squares[index] = Square::Exploded;
+            self.status = Status::Lost;
+            return;
+        }
+
+        let mut queue = VecDeque::from([(row, col)]);
+        while let Some((row, col)) = queue.pop_front() {
+            let index = self.index(row, col);
+            if self.squares[index] != Square::Hidden {
+                continue;
+            }
+            let count = self.board.count(row, col);
+            self.squares[index] = Square::Revealed(count);
+            self.revealed += 1;
+            if count == 0 {
+                queue.extend(
+                    self.board
+                        .neighbors(row, col)
+                        .into_iter()
+                        .filter(|&(row, col)| self.square(row, col) == Square::Hidden),
+                );
+            }
+        }
+
+        if self.revealed + self.mines == self.squares.len() {
+            self.status = Status::Won;
+        }
+    }
+
+    /// Lay the mines anywhere but `(row, col)` and, if there's room, its
+    /// neighbors.
+    fn lay_mines(&mut self, mut rng: Rng, row: usize, col: usize) {
+        let mut safe = self.board.neighbors(row, col);
+        safe.push((row, col));
+        let squares = self.squares.len();
+        if squares - safe.len() < self.mines {
+            safe = vec![(row, col)];
+        }
+        let safe = safe
+            .into_iter()
+            .map(|(row, col)| self.index(row, col))
+            .collect::<Vec<_>>();
+
+        let mut candidates = (0..squares)
+            .filter(|index| !safe.contains(index))
+            .collect::<Vec<_>>();
+        rng.shuffle(&mut candidates);
+        for &index in &candidates[..self.mines] {
+            self.board
+                .set_mine(index / self.width(), index % self.width(), true);
+        }
+    }
+
+    fn index(&self, row: usize, col: usize) -> usize {
+        assert!(
+            row < self.height() && col < self.width(),
+            "({row}, {col}) is off the board"
+        );
+        row * self.width() + col
+    }
+}
+
+/// Whether a `width` by `height` board has room for `mines` mines and at
+/// least one square without, as [`Game::new`] needs.
+pub(crate) fn has_safe_square(width: usize, height: usize, mines: usize) -> bool {
+    width
+        .checked_mul(height)
+        .is_some_and(|squares| mines < squares)
+}
+
+#[test]
+fn first_reveal_is_always_safe() {
+    for seed in 0..50 {
+        let mut game = Game::new(9, 9, 10, seed);
+        assert_ne!(game.reveal(4, 4), Ok(Status::Lost));
+        assert_eq!(game.square(4, 4), Square::Revealed(0));
+        assert_eq!(game.board().mine_count(), 10);
+    }
+    // No room to keep the neighbors clear as well: only the square itself.
+    let mut game = Game::new(3, 3, 8, 1);
+    assert_eq!(game.reveal(1, 1), Ok(Status::Won));
+    assert_eq!(game.square(1, 1), Square::Revealed(8));
+}
+
+#[test]
+fn seed_decides_the_board() {
+    let board = |seed| {
+        let mut game = Game::new(16, 16, 40, seed);
+        game.reveal(0, 0).unwrap();
+        game.board().clone()
+    };
+    assert_eq!(board(3), board(3));
+    assert_ne!(board(3), board(4));
+}
+
+#[test]
+fn zeros_flood_fill() {
+    let board = Board::from_rows(&["    ", "    ", "   *", "  * "]);
+    let mut game = Game::from_board(board);
+    assert_eq!(game.reveal(0, 0), Ok(Status::Playing));
+    assert_eq!(game.square(0, 0), Square::Revealed(0));
+    assert_eq!(game.square(1, 2), Square::Revealed(1));
+    assert_eq!(game.square(2, 1), Square::Revealed(1));
+    assert_eq!(game.square(2, 2), Square::Revealed(2));
+    // Cut off by the mines.
+    assert_eq!(game.square(3, 3), Square::Hidden);
+    assert_eq!(game.reveal(3, 3), Ok(Status::Won));
+}
+
+#[test]
+fn flags_block_reveals_and_chords_open_the_rest() {
+    let board = Board::from_rows(&["*  ", "   ", "  *"]);
+    let mut game = Game::from_board(board);
+    game.reveal(1, 1).unwrap();
+    assert_eq!(game.square(1, 1), Square::Revealed(2));
+
+    game.flag(0, 0).unwrap();
+    assert_eq!(game.reveal(0, 0), Ok(Status::Playing));
+    assert_eq!(game.square(0, 0), Square::Flagged);
+    assert_eq!(game.mines_left(), 1);
+
+    // One flag short: nothing happens.
+    game.chord(1, 1).unwrap();
+    assert_eq!(game.square(0, 1), Square::Hidden);
+
+    game.flag(2, 2).unwrap();
+    assert_eq!(game.chord(1, 1), Ok(Status::Won));
+    assert_eq!(game.square(0, 1), Square::Revealed(1));
+    assert_eq!(game.mines_left(), 0);
+}
+
+#[test]
+fn wrong_flags_make_a_chord_explode() {
+    let board = Board::from_rows(&["*  ", "   ", "   "]);
+    let mut game = Game::from_board(board);
+    game.reveal(1, 1).unwrap();
+    game.flag(0, 1).unwrap();
+    assert_eq!(game.chord(1, 1), Ok(Status::Lost));
+    assert_eq!(game.square(0, 0), Square::Exploded);
+    assert_eq!(game.reveal(2, 2), Err(MoveError::GameOver));
+}
+
+#[test]
+fn history_records_every_move_played() {
+    let mut game = Game::from_board(Board::from_rows(&["* ", "  "]));
+    game.flag(0, 0).unwrap();
+    game.flag(0, 0).unwrap();
+    assert_eq!(game.reveal(5, 0), Err(MoveError::OffBoard));
+    game.reveal(1, 1).unwrap();
+    game.reveal(1, 1).unwrap();
+    assert_eq!(
+        game.history(),
+        [
+            Move::Flag(0, 0),
+            Move::Flag(0, 0),
+            Move::Reveal(1, 1),
+            Move::Reveal(1, 1)
+        ]
+    );
+}
+
+#[test]
+fn torus_game() {
+    let mut game = Game::new(5, 5, 3, 9).with_topology(Topology::Torus);
+    game.reveal(0, 0).unwrap();
+    // Every neighbor of the first square, across the edges too, is safe.
+    for (row, col) in game.board().neighbors(0, 0) {
+        assert!(!game.board().is_mine(row, col));
+    }
+    assert!(game.board().neighbors(0, 0).contains(&(4, 4)));
+}
@@ -0,0 +1,170 @@
+use super::board::Board;
+use super::game::{has_safe_square, Game, Status};
+use super::rng::Rng;
+use super::solver::{solve_with, Rule};
+
+/// Boards tried before [`generate`] gives up.
+pub const ATTEMPTS: usize = 1_000;
+
+/// How hard a board is to clear without guessing, by the strongest technique
+/// it can't do without.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    /// Counts alone: every hidden neighbor is a mine, or none is.
+    Easy,
+    /// Comparing overlapping counts.
+    Medium,
+    /// Weighing every placement of mines, the total number of mines included.
+    Hard,
+}
+
+impl From<Rule> for Difficulty {
+    fn from(rule: Rule) -> Self {
+        match rule {
+            Rule::SingleCell => Self::Easy,
+            Rule::Subset => Self::Medium,
+            Rule::Enumeration => Self::Hard,
+        }
+    }
+}
+
+/// A board that can be cleared by logic alone from its first click.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub board: Board,
+    pub first: (usize, usize),
+    pub difficulty: Difficulty,
+    /// Rounds of deduction needed after the first click.
+    pub steps: usize,
+    /// `Game::new` with this seed and the same size and mine count lays the
+    /// same board once `first` is revealed.
+    pub seed: u64,
+}
+
+/// Grade `board` when started from `first`: play it out revealing only
+/// proven safe squares, each round with the simplest technique that finds
+/// any. `None` if that gets stuck, or `first` is a mine.
+pub fn grade(board: &Board, first: (usize, usize)) -> Option<(Difficulty, usize)> {
+    let mut game = Game::from_board(board.clone());
+    game.reveal(first.0, first.1).ok()?;
+    clear(game)
+}
+
+/// A no-guess board from the first of [`ATTEMPTS`] seeds derived from `seed`
+/// that gives one, at any difficulty. `None` straight away if `first` is off
+/// the board or there are too many mines to leave it safe.
+pub fn generate(
+    width: usize,
+    height: usize,
+    mines: usize,
+    first: (usize, usize),
+    seed: u64,
+) -> Option<Puzzle> {
+    attempts(width, height, mines, first, seed).next()
+}
+
+/// Like [`generate`], but only accepting a board of exactly `difficulty`.
+pub fn generate_graded(
+    width: usize,
+    height: usize,
+    mines: usize,
+    first: (usize, usize),
+    seed: u64,
+    difficulty: Difficulty,
+) -> Option<Puzzle> {
+    attempts(width, height, mines, first, seed).find(|puzzle| puzzle.difficulty == difficulty)
+}
+
+fn attempts(
+    width: usize,
+    height: usize,
+    mines: usize,
+    first: (usize, usize),
+    seed: u64,
+) -> impl Iterator<Item = Puzzle> {
+    let playable = has_safe_square(width, height, mines) && first.0 < height && first.1 < width;
+    let mut seeds = Rng::new(seed);
+    (0..if playable { ATTEMPTS } else { 0 }).filter_map(move |_| {
+        let seed = seeds.next_u64();
+        let mut game = Game::new(width, height, mines, seed);
+        game.reveal(first.0, first.1).ok()?;
+        let board = game.board().clone();
+        let (difficulty, steps) = clear(game)?;
+        Some(Puzzle {
+            board,
+            first,
+            difficulty,
+            steps,
+            seed,
+        })
+    })
+}
+
+fn clear(mut game: Game) -> Option<(Difficulty, usize)> {
+    let mut difficulty = Difficulty::Easy;
+    let mut steps = 0;
+    while game.status() == Status::Playing {
+        let (rule, safe) = [Rule::SingleCell, Rule::Subset, Rule::Enumeration]
+            .into_iter()
+            .find_map(|rule| {
+                let safe = solve_with(&game, rule)?.safe().collect::<Vec<_>>();
+                (!safe.is_empty()).then_some((rule, safe))
+            })?;
+        difficulty = difficulty.max(rule.into());
+        steps += 1;
+        for (row, col) in safe {
+            game.reveal(row, col).ok()?;
+        }
+    }
+    (game.status() == Status::Won).then_some((difficulty, steps))
+}
+
+#[test]
+fn generated_boards_clear_without_guessing() {
+    for seed in 0..5 {
+        let puzzle = generate(9, 9, 10, (4, 4), seed).unwrap();
+        assert_eq!(puzzle.board.mine_count(), 10);
+        assert!(!puzzle.board.is_mine(4, 4));
+        assert_eq!(
+            grade(&puzzle.board, puzzle.first),
+            Some((puzzle.difficulty, puzzle.steps))
+        );
+
+        let mut game = Game::new(9, 9, 10, puzzle.seed);
+        game.reveal(4, 4).unwrap();
+        assert_eq!(game.board(), &puzzle.board);
+    }
+    assert_eq!(generate(9, 9, 10, (0, 0), 1), generate(9, 9, 10, (0, 0), 1));
+}
+
+#[test]
+fn unplayable_boards_generate_nothing() {
+    assert_eq!(generate(3, 3, 9, (0, 0), 1), None);
+    assert_eq!(generate(3, 3, 100, (0, 0), 1), None);
+    assert_eq!(generate(9, 9, 10, (9, 0), 1), None);
+    assert_eq!(generate(9, 9, 10, (0, 9), 1), None);
+    assert_eq!(generate_graded(3, 3, 9, (0, 0), 1, Difficulty::Easy), None);
+}
+
+#[test]
+fn graded_generation() {
+    for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+        let puzzle = generate_graded(9, 9, 10, (4, 4), 7, difficulty).unwrap();
+        assert_eq!(puzzle.difficulty, difficulty);
+    }
+    assert!(Difficulty::Easy < Difficulty::Medium && Difficulty::Medium < Difficulty::Hard);
+}
+
+#[test]
+fn grading_hand_made_boards() {
+    // One click opens everything.
+    let board = Board::from_rows(&["*  ", "   ", "   "]);
+    assert_eq!(grade(&board, (2, 2)), Some((Difficulty::Easy, 0)));
+    // The 1-2-1 pattern wants the subset rule.
+    let board = Board::from_rows(&["   ", "   ", "* *"]);
+    assert_eq!(grade(&board, (0, 0)), Some((Difficulty::Medium, 1)));
+    // A coin flip.
+    let board = Board::from_rows(&["* ", "  "]);
+    assert_eq!(grade(&board, (1, 1)), None);
+    assert_eq!(grade(&board, (0, 0)), None);
+}
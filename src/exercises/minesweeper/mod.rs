@@ -1,58 +1,28 @@
-mod minesweeper {
-    static DIRS: [[i32; 2]; 8] = [
-        [-1, -1],
-        [-1, 0],
-        [-1, 1],
-        [0, -1],
-        [0, 1],
-        [1, -1],
-        [1, 0],
-        [1, 1],
-    ];
+pub mod bitset;
+pub mod board;
+pub mod cube;
+pub mod field;
+pub mod game;
+pub mod generator;
+pub mod render;
+pub mod replay;
+pub mod rng;
+pub mod solver;
 
-    pub fn annotate(minefield: &[&str]) -> Vec<String> {
-        let board = minefield
-            .iter()
-            .map(|line| line.as_bytes())
-            .collect::<Vec<_>>();
-
-        board
-            .iter()
-            .enumerate()
-            .map(|(y, line)| {
-                let stat = line
-                    .iter()
-                    .enumerate()
-                    .map(|(x, &code)| {
-                        if code == b'*' {
-                            return code;
-                        }
-                        let count = DIRS
-                            .iter()
-                            .filter(|[dx, dy]| {
-                                x as i32 + dx >= 0
-                                    && x as i32 + dx < line.len() as i32
-                                    && y as i32 + dy >= 0
-                                    && y as i32 + dy < board.len() as i32
-                            })
-                            .map(|[dx, dy]| {
-                                let nx = x as i32 + dx;
-                                let ny = y as i32 + dy;
-                                board[ny as usize][nx as usize]
-                            })
-                            .filter(|&code| code == b'*')
-                            .count() as u8;
-                        if count > 0 {
-                            b'0' + count
-                        } else {
-                            b' '
-                        }
-                    })
-                    .collect::<Vec<_>>();
+pub mod minesweeper {
+    use super::board::Board;
 
-                String::from_utf8(stat).unwrap()
-            })
-            .collect::<Vec<_>>()
+    /// Classic Minesweeper: the eight surrounding squares, hard edges.
+    ///
+    /// Never panics. Any char other than `*` counts as a blank, and a row
+    /// shorter than the others simply has no squares past its end.
+    pub fn annotate(minefield: &[&str]) -> Vec<String> {
+        Board::from_rows(minefield)
+            .annotate()
+            .into_iter()
+            .zip(minefield)
+            .map(|(annotated, row)| annotated.chars().take(row.chars().count()).collect())
+            .collect()
     }
 }
 
@@ -193,3 +163,8 @@ fn large_board() {
         "111111",
     ]);
 }
+#[test]
+fn ragged_and_non_ascii_rows_do_not_panic() {
+    assert_eq!(annotate(&["*", "  ", " "]), ["*", "11", " "]);
+    assert_eq!(annotate(&["é*", "", "x"]), ["1*", "", " "]);
+}
@@ -0,0 +1,447 @@
+use std::fmt::{self, Write};
+
+use super::board::Board;
+use super::cube::{Cube, Layer};
+use super::field::count_symbol;
+use super::game::{Game, Square};
+
+/// One square as drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Mine,
+    /// A square without a mine, and how many of its neighbors have one.
+    Count(usize),
+    Hidden,
+    Flagged,
+    Exploded,
+}
+
+impl Cell {
+    /// The char [`annotate`](super::minesweeper::annotate) would use, with
+    /// `#` for hidden squares, `F` for flags and `X` for an exploded mine.
+    pub fn symbol(self) -> char {
+        match self {
+            Self::Mine => '*',
+            Self::Count(count) => count_symbol(count),
+            Self::Hidden => '#',
+            Self::Flagged => 'F',
+            Self::Exploded => 'X',
+        }
+    }
+}
+
+/// Anything that can be drawn square by square: a solved [`Board`], or a
+/// [`Game`] as its player sees it.
+pub trait Grid {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn cell(&self, row: usize, col: usize) -> Cell;
+}
+
+impl Grid for Board {
+    fn width(&self) -> usize {
+        self.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height()
+    }
+
+    fn cell(&self, row: usize, col: usize) -> Cell {
+        if self.is_mine(row, col) {
+            Cell::Mine
+        } else {
+            Cell::Count(self.count(row, col))
+        }
+    }
+}
+
+impl Grid for Game {
+    fn width(&self) -> usize {
+        self.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height()
+    }
+
+    fn cell(&self, row: usize, col: usize) -> Cell {
+        match self.square(row, col) {
+            Square::Hidden => Cell::Hidden,
+            Square::Flagged => Cell::Flagged,
+            Square::Revealed(count) => Cell::Count(count),
+            Square::Exploded => Cell::Exploded,
+        }
+    }
+}
+
+impl Grid for Layer<'_> {
+    fn width(&self) -> usize {
+        self.cube().width()
+    }
+
+    fn height(&self) -> usize {
+        self.cube().height()
+    }
+
+    fn cell(&self, row: usize, col: usize) -> Cell {
+        if self.cube().is_mine(self.index(), row, col) {
+            Cell::Mine
+        } else {
+            Cell::Count(self.cube().count(self.index(), row, col))
+        }
+    }
+}
+
+/// Rows of [`Cell::symbol`]s, one per line.
+pub fn plain(grid: &impl Grid) -> String {
+    rows(grid, |out, cell| out.push(cell.symbol()))
+}
+
+/// For terminals, with counts in the classic colors: blue, green, red, navy,
+/// maroon, teal, then bold and gray. The original draws 7 in black, which
+/// vanishes on a dark background.
+pub fn ansi(grid: &impl Grid) -> String {
+    const RESET: &str = "\x1b[0m";
+    rows(grid, |out, cell| {
+        let style = match cell {
+            Cell::Count(1) => "\x1b[94m",
+            Cell::Count(2) => "\x1b[32m",
+            Cell::Count(3) => "\x1b[91m",
+            Cell::Count(4) => "\x1b[34m",
+            Cell::Count(5) => "\x1b[31m",
+            Cell::Count(6) => "\x1b[36m",
+            Cell::Count(7) => "\x1b[1m",
+            Cell::Count(8) => "\x1b[90m",
+            Cell::Mine => "\x1b[1m",
+            Cell::Hidden => "\x1b[2m",
+            Cell::Flagged => "\x1b[1;91m",
+            Cell::Exploded => "\x1b[1;97;41m",
+            Cell::Count(_) => "",
+        };
+        if style.is_empty() {
+            out.push(cell.symbol());
+        } else {
+            let _ = write!(out, "{style}{}{RESET}", cell.symbol());
+        }
+    })
+}
+
+/// A grid drawn with Unicode box-drawing characters. Empty for an empty
+/// board.
+pub fn box_drawing(grid: &impl Grid) -> String {
+    let (width, height) = (grid.width(), grid.height());
+    if width == 0 || height == 0 {
+        return String::new();
+    }
+    let rule = |left: char, middle: char, right: char| {
+        let mut line = String::from(left);
+        for col in 0..width {
+            line.push_str("───");
+            line.push(if col + 1 == width { right } else { middle });
+        }
+        line
+    };
+
+    let mut out = rule('┌', '┬', '┐');
+    for row in 0..height {
+        out.push_str("\n│");
+        for col in 0..width {
+            let _ = write!(out, " {} │", grid.cell(row, col).symbol());
+        }
+        out.push('\n');
+        if row + 1 == height {
+            out.push_str(&rule('└', '┴', '┘'));
+        } else {
+            out.push_str(&rule('├', '┼', '┤'));
+        }
+    }
+    out
+}
+
+/// For boards with [`Neighborhood::Hex`](super::board::Neighborhood::Hex):
+/// squares a blank apart, every row shifted right by half a square more
+/// than the one above, so that neighbors touch.
+pub fn hex(grid: &impl Grid) -> String {
+    let mut out = String::new();
+    for row in 0..grid.height() {
+        if row > 0 {
+            out.push('\n');
+        }
+        out.extend(std::iter::repeat_n(' ', row));
+        for col in 0..grid.width() {
+            if col > 0 {
+                out.push(' ');
+            }
+            out.push(grid.cell(row, col).symbol());
+        }
+    }
+    out
+}
+
+/// A `<table class="minesweeper">` with a class on every cell for styling:
+/// `mine`, `blank`, `n1` to `n8` and so on, `hidden`, `flagged` or
+/// `exploded`.
+pub fn html(grid: &impl Grid) -> String {
+    let mut out = String::from("<table class=\"minesweeper\">\n");
+    for row in 0..grid.height() {
+        out.push_str("<tr>");
+        for col in 0..grid.width() {
+            let cell = grid.cell(row, col);
+            let class = match cell {
+                Cell::Mine => "mine".to_owned(),
+                Cell::Count(0) => "blank".to_owned(),
+                Cell::Count(count) => format!("n{count}"),
+                Cell::Hidden => "hidden".to_owned(),
+                Cell::Flagged => "flagged".to_owned(),
+                Cell::Exploded => "exploded".to_owned(),
+            };
+            let text = match cell {
+                Cell::Count(0) | Cell::Hidden => String::new(),
+                Cell::Count(count) => count.to_string(),
+                _ => cell.symbol().to_string(),
+            };
+            let _ = write!(out, "<td class=\"{class}\">{text}</td>");
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>");
+    out
+}
+
+fn rows(grid: &impl Grid, mut draw: impl FnMut(&mut String, Cell)) -> String {
+    let mut out = String::new();
+    for row in 0..grid.height() {
+        if row > 0 {
+            out.push('\n');
+        }
+        for col in 0..grid.width() {
+            draw(&mut out, grid.cell(row, col));
+        }
+    }
+    out
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&plain(self))
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&plain(self))
+    }
+}
+
+/// Layer after layer, a blank line between each.
+impl fmt::Display for Cube {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for layer in 0..self.depth() {
+            if layer > 0 {
+                f.write_str("\n\n")?;
+            }
+            f.write_str(&plain(&self.layer(layer)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "json")]
+pub use self::json::{from_json, to_json, JsonError};
+
+#[cfg(feature = "json")]
+mod json {
+    use std::fmt;
+
+    use serde_json::{json, Value};
+
+    use super::super::board::{Board, Neighborhood, ParseError, Topology};
+
+    #[derive(Debug)]
+    pub enum JsonError {
+        Json(serde_json::Error),
+        /// A field is missing or holds the wrong kind of value.
+        Field(&'static str),
+        Board(ParseError),
+    }
+
+    impl fmt::Display for JsonError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Self::Json(error) => write!(f, "invalid JSON: {error}"),
+                Self::Field(field) => write!(f, "missing or invalid field {field:?}"),
+                Self::Board(error) => write!(f, "invalid board: {error}"),
+            }
+        }
+    }
+
+    impl std::error::Error for JsonError {}
+
+    /// The mines as rows in the format [`Board::parse`] reads, plus the
+    /// neighborhood and topology unless they're the classic ones:
+    /// `{"rows":["* ","  "],"topology":"torus"}`.
+    pub fn to_json(board: &Board) -> String {
+        let rows = (0..board.height())
+            .map(|row| {
+                (0..board.width())
+                    .map(|col| if board.is_mine(row, col) { '*' } else { ' ' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        let mut value = json!({ "rows": rows });
+        let neighborhood = match board.neighborhood() {
+            Neighborhood::Moore => None,
+            Neighborhood::VonNeumann => Some(json!("von_neumann")),
+            Neighborhood::Knight => Some(json!("knight")),
+            Neighborhood::Hex => Some(json!("hex")),
+            Neighborhood::Custom(offsets) => Some(json!(offsets)),
+        };
+        if let Some(neighborhood) = neighborhood {
+            value["neighborhood"] = neighborhood;
+        }
+        if board.topology() == Topology::Torus {
+            value["topology"] = json!("torus");
+        }
+        value.to_string()
+    }
+
+    /// Read back what [`to_json`] wrote.
+    pub fn from_json(json: &str) -> Result<Board, JsonError> {
+        let value = serde_json::from_str::<Value>(json).map_err(JsonError::Json)?;
+        let rows = value["rows"]
+            .as_array()
+            .ok_or(JsonError::Field("rows"))?
+            .iter()
+            .map(|row| row.as_str().ok_or(JsonError::Field("rows")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let neighborhood = match &value["neighborhood"] {
+            Value::Null => Neighborhood::Moore,
+            Value::String(name) if name == "moore" => Neighborhood::Moore,
+            Value::String(name) if name == "von_neumann" => Neighborhood::VonNeumann,
+            Value::String(name) if name == "knight" => Neighborhood::Knight,
+            Value::String(name) if name == "hex" => Neighborhood::Hex,
+            offsets @ Value::Array(_) => Neighborhood::Custom(
+                serde_json::from_value(offsets.clone())
+                    .map_err(|_| JsonError::Field("neighborhood"))?,
+            ),
+            _ => return Err(JsonError::Field("neighborhood")),
+        };
+        let topology = match value["topology"].as_str() {
+            None if value["topology"].is_null() => Topology::Bounded,
+            Some("bounded") => Topology::Bounded,
+            Some("torus") => Topology::Torus,
+            _ => return Err(JsonError::Field("topology")),
+        };
+        Ok(Board::parse(&rows)
+            .map_err(JsonError::Board)?
+            .with_neighborhood(neighborhood)
+            .with_topology(topology))
+    }
+}
+
+#[test]
+fn plain_matches_annotate() {
+    let rows = ["*  ", "  *"];
+    let board = Board::parse(&rows).unwrap();
+    assert_eq!(
+        plain(&board),
+        super::minesweeper::annotate(&rows).join("\n")
+    );
+    assert_eq!(board.to_string(), "*21\n12*");
+}
+
+#[test]
+fn game_views() {
+    let mut game = Game::from_board(Board::parse(&["*  ", "   ", "  *"]).unwrap());
+    game.reveal(0, 2).unwrap();
+    game.flag(0, 0).unwrap();
+    assert_eq!(game.to_string(), "F1 \n#21\n###");
+    game.reveal(2, 2).unwrap();
+    assert_eq!(plain(&game), "F1 \n#21\n##X");
+}
+
+#[test]
+fn ansi_colors_counts() {
+    let board = Board::parse(&["* "]).unwrap();
+    assert_eq!(ansi(&board), "\x1b[1m*\x1b[0m\x1b[94m1\x1b[0m");
+    let board = Board::parse(&["***", "* *", "***"]).unwrap();
+    assert!(ansi(&board).contains("\x1b[90m8\x1b[0m"));
+    assert_eq!(ansi(&Board::parse(&["  "]).unwrap()), "  ");
+}
+
+#[test]
+fn box_drawing_grid() {
+    let board = Board::parse(&["* ", "  "]).unwrap();
+    let expected = "\
+┌───┬───┐
+│ * │ 1 │
+├───┼───┤
+│ 1 │ 1 │
+└───┴───┘";
+    assert_eq!(box_drawing(&board), expected);
+    assert_eq!(box_drawing(&Board::new(0, 3)), "");
+}
+
+#[test]
+fn html_table() {
+    let board = Board::parse(&["* "]).unwrap();
+    assert_eq!(
+        html(&board),
+        "<table class=\"minesweeper\">\n\
+         <tr><td class=\"mine\">*</td><td class=\"n1\">1</td></tr>\n\
+         </table>"
+    );
+}
+
+#[test]
+fn hex_and_cube_layouts() {
+    use super::board::Neighborhood;
+
+    let board = Board::parse(&["   ", " * ", "   "])
+        .unwrap()
+        .with_neighborhood(Neighborhood::Hex);
+    assert_eq!(hex(&board), "  1 1\n 1 * 1\n  1 1  ");
+
+    let cube = Cube::parse(&[&["* "], &["  "]]).unwrap();
+    assert_eq!(cube.to_string(), "*1\n\n11");
+    assert_eq!(
+        box_drawing(&cube.layer(1)),
+        "┌───┬───┐\n│ 1 │ 1 │\n└───┴───┘"
+    );
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn json_round_trips() {
+    use super::board::{Neighborhood, Topology};
+
+    let board = Board::parse(&["* ", " *", "  "]).unwrap();
+    assert_eq!(to_json(&board), r#"{"rows":["* "," *","  "]}"#);
+    assert_eq!(from_json(&to_json(&board)).unwrap(), board);
+
+    let board = board
+        .with_neighborhood(Neighborhood::Custom(vec![(0, 1), (-2, 0)]))
+        .with_topology(Topology::Torus);
+    assert_eq!(from_json(&to_json(&board)).unwrap(), board);
+    for neighborhood in [Neighborhood::Knight, Neighborhood::Hex] {
+        let board = board.clone().with_neighborhood(neighborhood);
+        assert_eq!(from_json(&to_json(&board)).unwrap(), board);
+    }
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn json_errors() {
+    assert!(matches!(from_json("{"), Err(JsonError::Json(_))));
+    assert!(matches!(from_json("{}"), Err(JsonError::Field("rows"))));
+    assert!(matches!(
+        from_json(r#"{"rows":["*"],"topology":"sphere"}"#),
+        Err(JsonError::Field("topology"))
+    ));
+    let error = from_json(r#"{"rows":["* ","x "]}"#).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid board: row 1, column 0: 'x' is neither a mine nor a blank"
+    );
+}
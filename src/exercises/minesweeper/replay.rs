@@ -0,0 +1,654 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use super::board::{Board, Neighborhood, ParseError, Topology};
+use super::game::{has_safe_square, Game, Move, MoveError, Square, Status};
+
+const HEADER: &str = "minesweeper replay";
+
+/// The most squares a written replay may ask for, so one from elsewhere
+/// can't make parsing allocate without bound.
+pub const MAX_SQUARES: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// A line of a written replay that can't be read, or a board of more
+    /// than [`MAX_SQUARES`]. Lines count from 0.
+    Malformed {
+        line: usize,
+    },
+    /// A written replay without a line it needs, such as `size`.
+    Missing(&'static str),
+    Board(ParseError),
+    /// Playback rejected the move at `index`.
+    Move {
+        index: usize,
+        error: MoveError,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Malformed { line } => write!(f, "line {line} is malformed"),
+            Self::Missing(what) => write!(f, "no {what} line"),
+            Self::Board(error) => write!(f, "invalid board: {error}"),
+            Self::Move { index, error } => write!(f, "move {index}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Everything needed to play a game again move for move: how its mines were
+/// laid, and each move with the time since the start of the game.
+///
+/// Written out, a replay is a few lines of text:
+///
+/// ```text
+/// minesweeper replay
+/// size 9 9 10
+/// seed 42
+/// moves
+/// 0 r 4 4
+/// 1520 f 0 3
+/// ```
+///
+/// `size` is width, height and mines. A game with its mines laid in advance
+/// has `board` and a row per line instead of `seed`, with `.` for blanks.
+/// `neighborhood` and `topology` lines follow `size` when they're not the
+/// classic ones. Moves are milliseconds, then `r`, `f` or `c` for reveal,
+/// flag or chord, then row and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    mines: usize,
+    seed: Option<u64>,
+    /// Always the neighbors and topology; the mines as well unless `seed`
+    /// lays them.
+    board: Board,
+    moves: Vec<(Duration, Move)>,
+}
+
+impl Replay {
+    /// A game laid out by `seed` on the first reveal, like [`Game::new`].
+    ///
+    /// # Panics
+    ///
+    /// If there wouldn't be a single safe square.
+    pub fn seeded(width: usize, height: usize, mines: usize, seed: u64) -> Self {
+        assert!(
+            has_safe_square(width, height, mines),
+            "{mines} mines leave no safe square on a {width} by {height} board"
+        );
+        Self {
+            mines,
+            seed: Some(seed),
+            board: Board::new(width, height),
+            moves: Vec::new(),
+        }
+    }
+
+    /// A game on a board whose mines are already laid, like
+    /// [`Game::from_board`].
+    pub fn from_board(board: Board) -> Self {
+        Self {
+            mines: board.mine_count(),
+            seed: None,
+            board,
+            moves: Vec::new(),
+        }
+    }
+
+    pub fn with_neighborhood(mut self, neighborhood: Neighborhood) -> Self {
+        self.board = self.board.with_neighborhood(neighborhood);
+        self
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.board = self.board.with_topology(topology);
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.board.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.board.height()
+    }
+
+    pub fn mine_count(&self) -> usize {
+        self.mines
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn moves(&self) -> &[(Duration, Move)] {
+        &self.moves
+    }
+
+    /// Add a move played `at` since the start. Times are kept to the
+    /// millisecond, and one earlier than the last move's is taken to be the
+    /// same as that.
+    pub fn push(&mut self, at: Duration, mv: Move) {
+        let at = Duration::from_millis(at.as_millis() as u64);
+        let at = self.moves.last().map_or(at, |&(last, _)| at.max(last));
+        self.moves.push((at, mv));
+    }
+
+    /// The game before the first move.
+    pub fn game(&self) -> Game {
+        match self.seed {
+            Some(seed) => Game::new(self.width(), self.height(), self.mines, seed)
+                .with_neighborhood(self.board.neighborhood().clone())
+                .with_topology(self.board.topology()),
+            None => Game::from_board(self.board.clone()),
+        }
+    }
+
+    /// The game after every move. The same replay always ends in the same
+    /// game.
+    pub fn play(&self) -> Result<Game, ReplayError> {
+        self.play_until(Duration::MAX)
+    }
+
+    /// The game after the moves played up to and including `at`.
+    pub fn play_until(&self, at: Duration) -> Result<Game, ReplayError> {
+        let mut game = self.game();
+        for (index, &(time, mv)) in self.moves.iter().enumerate() {
+            if time > at {
+                break;
+            }
+            game.play(mv)
+                .map_err(|error| ReplayError::Move { index, error })?;
+        }
+        Ok(game)
+    }
+
+    pub fn stats(&self) -> Result<Stats, ReplayError> {
+        let game = self.play()?;
+        let units = three_bv_units(game.board());
+        let solved = units
+            .iter()
+            .filter(|&&(row, col)| matches!(game.square(row, col), Square::Revealed(_)))
+            .count();
+        Ok(Stats {
+            three_bv: units.len(),
+            solved_three_bv: solved,
+            clicks: self.moves.len(),
+            time: self.moves.last().map_or(Duration::ZERO, |&(at, _)| at),
+            status: game.status(),
+        })
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        writeln!(f, "size {} {} {}", self.width(), self.height(), self.mines)?;
+        match self.board.neighborhood() {
+            Neighborhood::Moore => {}
+            Neighborhood::VonNeumann => writeln!(f, "neighborhood von_neumann")?,
+            Neighborhood::Knight => writeln!(f, "neighborhood knight")?,
+            Neighborhood::Hex => writeln!(f, "neighborhood hex")?,
+            Neighborhood::Custom(offsets) => {
+                write!(f, "neighborhood custom")?;
+                for (dr, dc) in offsets {
+                    write!(f, " {dr},{dc}")?;
+                }
+                writeln!(f)?;
+            }
+        }
+        if self.board.topology() == Topology::Torus {
+            writeln!(f, "topology torus")?;
+        }
+        match self.seed {
+            Some(seed) => writeln!(f, "seed {seed}")?,
+            None => {
+                writeln!(f, "board")?;
+                for row in 0..self.height() {
+                    let line = (0..self.width())
+                        .map(|col| {
+                            if self.board.is_mine(row, col) {
+                                '*'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect::<String>();
+                    writeln!(f, "{line}")?;
+                }
+            }
+        }
+        writeln!(f, "moves")?;
+        for &(at, mv) in &self.moves {
+            let kind = match mv {
+                Move::Reveal(..) => 'r',
+                Move::Flag(..) => 'f',
+                Move::Chord(..) => 'c',
+            };
+            let (row, col) = mv.square();
+            writeln!(f, "{} {kind} {row} {col}", at.as_millis())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Replay {
+    type Err = ReplayError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut lines = input.lines().enumerate();
+        let malformed = |line| ReplayError::Malformed { line };
+        if lines.next().map(|(_, text)| text.trim_end()) != Some(HEADER) {
+            return Err(malformed(0));
+        }
+
+        let mut size = None;
+        let mut neighborhood = Neighborhood::default();
+        let mut topology = Topology::default();
+        let mut seed = None;
+        let mut board = None;
+        let mut moves = false;
+        while let Some((line, text)) = lines.next() {
+            let (key, value) = text.trim_end().split_once(' ').unwrap_or((text, ""));
+            match key {
+                "size" => {
+                    let [width, height, mines] = numbers::<3>(value).ok_or(malformed(line))?;
+                    if width
+                        .checked_mul(height)
+                        .is_none_or(|squares| squares > MAX_SQUARES)
+                    {
+                        return Err(malformed(line));
+                    }
+                    size = Some((line, [width, height, mines]));
+                }
+                "neighborhood" => {
+                    neighborhood = parse_neighborhood(value).ok_or(malformed(line))?
+                }
+                "topology" => {
+                    topology = match value {
+                        "bounded" => Topology::Bounded,
+                        "torus" => Topology::Torus,
+                        _ => return Err(malformed(line)),
+                    }
+                }
+                "seed" => seed = Some(value.parse().map_err(|_| malformed(line))?),
+                "board" => {
+                    let (_, [width, height, _]) = size.ok_or(ReplayError::Missing("size"))?;
+                    let rows = lines
+                        .by_ref()
+                        .take(height)
+                        .map(|(_, row)| row.trim_end().replace('.', " "))
+                        .collect::<Vec<_>>();
+                    let rows = rows.iter().map(String::as_str).collect::<Vec<_>>();
+                    let parsed = Board::parse(&rows).map_err(ReplayError::Board)?;
+                    if parsed.width() != width || parsed.height() != height {
+                        return Err(malformed(line));
+                    }
+                    board = Some(parsed);
+                }
+                "moves" => {
+                    moves = true;
+                    break;
+                }
+                _ => return Err(malformed(line)),
+            }
+        }
+        let (size_line, [width, height, mines]) = size.ok_or(ReplayError::Missing("size"))?;
+        if !moves {
+            return Err(ReplayError::Missing("moves"));
+        }
+        let replay = match (seed, board) {
+            (Some(_), _) if !has_safe_square(width, height, mines) => {
+                return Err(malformed(size_line))
+            }
+            (Some(seed), _) => Self::seeded(width, height, mines, seed),
+            (None, Some(board)) if board.mine_count() == mines => Self::from_board(board),
+            (None, Some(_)) => return Err(malformed(size_line)),
+            (None, None) => return Err(ReplayError::Missing("seed or board")),
+        };
+        let mut replay = replay
+            .with_neighborhood(neighborhood)
+            .with_topology(topology);
+
+        for (line, text) in lines {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let parts = text.split_whitespace().collect::<Vec<_>>();
+            let &[at, kind, row, col] = parts.as_slice() else {
+                return Err(malformed(line));
+            };
+            let [at, row, col] = [at, row, col].map(str::parse);
+            let (Ok(at), Ok(row), Ok(col)) = (at, row, col) else {
+                return Err(malformed(line));
+            };
+            let mv = match kind {
+                "r" => Move::Reveal(row, col),
+                "f" => Move::Flag(row, col),
+                "c" => Move::Chord(row, col),
+                _ => return Err(malformed(line)),
+            };
+            replay.push(Duration::from_millis(at as u64), mv);
+        }
+        Ok(replay)
+    }
+}
+
+/// Plays a game and writes its [`Replay`] as it goes, timing every move
+/// from when the recorder was made.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    game: Game,
+    replay: Replay,
+    start: Instant,
+    /// Time already on the clock when `start` was taken.
+    offset: Duration,
+}
+
+impl Recorder {
+    /// Pick up where `replay` ends, its clock carrying on from its last move.
+    pub fn new(replay: Replay) -> Result<Self, ReplayError> {
+        Ok(Self {
+            game: replay.play()?,
+            start: Instant::now(),
+            offset: replay.moves.last().map_or(Duration::ZERO, |&(at, _)| at),
+            replay,
+        })
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn into_replay(self) -> Replay {
+        self.replay
+    }
+
+    /// Play `mv` now. Only moves the game takes are recorded.
+    pub fn play(&mut self, mv: Move) -> Result<Status, MoveError> {
+        self.play_at(self.offset + self.start.elapsed(), mv)
+    }
+
+    /// Play `mv` as if `at` had passed since the start, for games timed by
+    /// some other clock.
+    pub fn play_at(&mut self, at: Duration, mv: Move) -> Result<Status, MoveError> {
+        let status = self.game.play(mv)?;
+        self.replay.push(at, mv);
+        Ok(status)
+    }
+}
+
+/// `N` numbers apart by blanks, and nothing else.
+fn numbers<const N: usize>(text: &str) -> Option<[usize; N]> {
+    let mut numbers = [0; N];
+    let mut parts = text.split_whitespace();
+    for number in &mut numbers {
+        *number = parts.next()?.parse().ok()?;
+    }
+    parts.next().is_none().then_some(numbers)
+}
+
+fn parse_neighborhood(text: &str) -> Option<Neighborhood> {
+    let (name, offsets) = text.split_once(' ').unwrap_or((text, ""));
+    match name {
+        "moore" => Some(Neighborhood::Moore),
+        "von_neumann" => Some(Neighborhood::VonNeumann),
+        "knight" => Some(Neighborhood::Knight),
+        "hex" => Some(Neighborhood::Hex),
+        "custom" => offsets
+            .split_whitespace()
+            .map(|offset| {
+                let (dr, dc) = offset.split_once(',')?;
+                Some((dr.parse().ok()?, dc.parse().ok()?))
+            })
+            .collect::<Option<_>>()
+            .map(Neighborhood::Custom),
+        _ => None,
+    }
+}
+
+/// How a game went, from its [`Replay`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// The board's 3BV: the fewest clicks that clear it without flags.
+    pub three_bv: usize,
+    /// The part of the 3BV the player got done.
+    pub solved_three_bv: usize,
+    /// Every move, flags and chords too.
+    pub clicks: usize,
+    /// Until the last move.
+    pub time: Duration,
+    pub status: Status,
+}
+
+impl Stats {
+    /// Solved 3BV per click: 1 for a perfect game, less for every wasted
+    /// click, and more for clever chording. 0 without clicks.
+    pub fn efficiency(&self) -> f64 {
+        if self.clicks == 0 {
+            return 0.0;
+        }
+        self.solved_three_bv as f64 / self.clicks as f64
+    }
+
+    /// 0 while no time has passed.
+    pub fn clicks_per_second(&self) -> f64 {
+        per_second(self.clicks, self.time)
+    }
+
+    pub fn three_bv_per_second(&self) -> f64 {
+        per_second(self.solved_three_bv, self.time)
+    }
+}
+
+fn per_second(count: usize, time: Duration) -> f64 {
+    if time.is_zero() {
+        return 0.0;
+    }
+    count as f64 / time.as_secs_f64()
+}
+
+/// 3BV, Bechtel's Board Benchmark Value: each opening, a patch of squares
+/// without neighboring mines, takes one click, and so does every other safe
+/// square that isn't on the edge of an opening.
+pub fn three_bv(board: &Board) -> usize {
+    three_bv_units(board).len()
+}
+
+/// A square for each click counted by [`three_bv`], revealed by that click.
+fn three_bv_units(board: &Board) -> Vec<(usize, usize)> {
+    let (width, height) = (board.width(), board.height());
+    let mut covered = vec![false; width * height];
+    let mut units = Vec::new();
+    let zero = |row, col| !board.is_mine(row, col) && board.count(row, col) == 0;
+
+    for row in 0..height {
+        for col in 0..width {
+            if covered[row * width + col] || !zero(row, col) {
+                continue;
+            }
+            units.push((row, col));
+            covered[row * width + col] = true;
+            let mut queue = VecDeque::from([(row, col)]);
+            while let Some((row, col)) = queue.pop_front() {
+                for (row, col) in board.neighbors(row, col) {
+                    let index = row * width + col;
+                    if !covered[index] {
+                        covered[index] = true;
+                        if zero(row, col) {
+                            queue.push_back((row, col));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    for row in 0..height {
+        for col in 0..width {
+            if !covered[row * width + col] && !board.is_mine(row, col) {
+                units.push((row, col));
+            }
+        }
+    }
+    units
+}
+
+#[test]
+fn seeded_games_play_back_the_same() {
+    let mut recorder = Recorder::new(Replay::seeded(9, 9, 10, 3)).unwrap();
+    recorder.play(Move::Reveal(4, 4)).unwrap();
+    assert_eq!(recorder.play(Move::Reveal(9, 0)), Err(MoveError::OffBoard));
+    let mut game = recorder.game().clone();
+    // Keep revealing whatever the solver proves safe.
+    while let Some(solution) = super::solver::solve(&game) {
+        let Some((row, col)) = solution.safe().next() else {
+            break;
+        };
+        recorder.play(Move::Reveal(row, col)).unwrap();
+        game = recorder.game().clone();
+    }
+    assert_eq!(game.status(), Status::Won);
+
+    let replay = recorder.into_replay();
+    let text = replay.to_string();
+    assert!(text.starts_with("minesweeper replay\nsize 9 9 10\nseed 3\nmoves\n"));
+    let parsed = text.parse::<Replay>().unwrap();
+    assert_eq!(parsed, replay);
+    let played = parsed.play().unwrap();
+    assert_eq!(played.board(), game.board());
+    assert_eq!(played.to_string(), game.to_string());
+    assert_eq!(played.history(), game.history());
+}
+
+#[test]
+fn board_replays_round_trip() {
+    let board = Board::parse(&["*  ", "   ", "  *"]).unwrap();
+    let mut replay = Replay::from_board(board)
+        .with_neighborhood(Neighborhood::Custom(vec![(-1, 0), (1, 1)]))
+        .with_topology(Topology::Torus);
+    replay.push(Duration::from_millis(250), Move::Flag(0, 0));
+    replay.push(Duration::from_micros(1_999), Move::Reveal(1, 1));
+    replay.push(Duration::from_millis(1), Move::Chord(1, 1));
+    let text = "\
+minesweeper replay
+size 3 3 2
+neighborhood custom -1,0 1,1
+topology torus
+board
+*..
+...
+..*
+moves
+250 f 0 0
+250 r 1 1
+250 c 1 1
+";
+    assert_eq!(replay.to_string(), text);
+    assert_eq!(text.parse::<Replay>().unwrap(), replay);
+
+    let before = replay.play_until(Duration::from_millis(249)).unwrap();
+    assert!(before.history().is_empty());
+    assert_eq!(replay.play().unwrap().history().len(), 3);
+}
+
+#[test]
+fn malformed_replays() {
+    let parse = |text: &str| text.parse::<Replay>().unwrap_err();
+    assert_eq!(parse("minesweeper\n"), ReplayError::Malformed { line: 0 });
+    // No square left to reveal safely.
+    assert_eq!(
+        parse("minesweeper replay\nsize 2 1 5\nseed 1\nmoves\n"),
+        ReplayError::Malformed { line: 1 }
+    );
+    assert_eq!(
+        parse("minesweeper replay\nsize 2 1 2\nseed 1\nmoves\n"),
+        ReplayError::Malformed { line: 1 }
+    );
+    // Too big to allocate, or to even count the squares of.
+    assert_eq!(
+        parse("minesweeper replay\nsize 100000 100000 1\nseed 1\nmoves\n"),
+        ReplayError::Malformed { line: 1 }
+    );
+    let overflowing = format!(
+        "minesweeper replay\nsize {} 2 1\nseed 1\nmoves\n",
+        usize::MAX
+    );
+    assert_eq!(parse(&overflowing), ReplayError::Malformed { line: 1 });
+    let largest = "minesweeper replay\nsize 1024 1024 1\nseed 1\nmoves\n";
+    assert_eq!(
+        largest.parse::<Replay>().map(|replay| replay.moves.len()),
+        Ok(0)
+    );
+    assert_eq!(
+        parse("minesweeper replay\nseed 1\nmoves\n"),
+        ReplayError::Missing("size")
+    );
+    assert_eq!(
+        parse("minesweeper replay\nsize 2 1 1\nmoves\n"),
+        ReplayError::Missing("seed or board")
+    );
+    assert_eq!(
+        parse("minesweeper replay\nsize 2 1 1\nseed 1\nmoves\n0 x 0 0\n"),
+        ReplayError::Malformed { line: 4 }
+    );
+    assert_eq!(
+        parse("minesweeper replay\nsize 2 1 2\nboard\n*.\nmoves\n"),
+        ReplayError::Malformed { line: 1 }
+    );
+    assert!(matches!(
+        parse("minesweeper replay\nsize 2 1 1\nboard\n*x\nmoves\n"),
+        ReplayError::Board(_)
+    ));
+
+    let mut replay = Replay::from_board(Board::parse(&["* "]).unwrap());
+    replay.push(Duration::ZERO, Move::Reveal(0, 0));
+    replay.push(Duration::ZERO, Move::Reveal(0, 1));
+    assert_eq!(
+        replay.play().unwrap_err(),
+        ReplayError::Move {
+            index: 1,
+            error: MoveError::GameOver
+        }
+    );
+}
+
+#[test]
+fn three_bv_counts_openings_and_lone_numbers() {
+    // One opening, and four numbers on the right that it doesn't reach.
+    let board = Board::parse(&["     ", "   * ", "     ", "    *"]).unwrap();
+    assert_eq!(board.annotate(), ["  111", "  1*1", "  122", "   1*"]);
+    assert_eq!(three_bv(&board), 5);
+    assert_eq!(three_bv(&Board::parse(&["* *"]).unwrap()), 1);
+    assert_eq!(three_bv(&Board::parse(&["   "]).unwrap()), 1);
+}
+
+#[test]
+fn stats_from_a_replay() {
+    let board = Board::parse(&["*  ", "   ", "   "]).unwrap();
+    let mut replay = Replay::from_board(board);
+    replay.push(Duration::from_millis(500), Move::Flag(0, 0));
+    replay.push(Duration::from_millis(1_000), Move::Reveal(2, 2));
+    let stats = replay.stats().unwrap();
+    assert_eq!(stats.three_bv, 1);
+    assert_eq!(stats.solved_three_bv, 1);
+    assert_eq!(stats.clicks, 2);
+    assert_eq!(stats.time, Duration::from_secs(1));
+    assert_eq!(stats.status, Status::Won);
+    assert_eq!(stats.efficiency(), 0.5);
+    assert_eq!(stats.clicks_per_second(), 2.0);
+    assert_eq!(stats.three_bv_per_second(), 1.0);
+
+    let stats = Replay::seeded(9, 9, 10, 0).stats().unwrap();
+    assert_eq!((stats.clicks, stats.efficiency()), (0, 0.0));
+    assert_eq!(stats.clicks_per_second(), 0.0);
+}
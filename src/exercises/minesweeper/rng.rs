@@ -0,0 +1,76 @@
+/// SplitMix64. Small and fast, and a seed gives the same sequence on every
+/// platform and in every version, which seeded games and replays rely on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..bound`, without modulo bias.
+    ///
+    /// # Panics
+    ///
+    /// If `bound` is zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "empty range");
+        let bound = bound as u64;
+        // Lemire's multiply-and-shift, redrawing the few values that would
+        // make some results more likely than others.
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let product = u128::from(self.next_u64()) * u128::from(bound);
+            if product as u64 >= threshold {
+                return (product >> 64) as usize;
+            }
+        }
+    }
+
+    /// Fisher-Yates, in place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for last in (1..items.len()).rev() {
+            items.swap(last, self.below(last + 1));
+        }
+    }
+}
+
+#[test]
+fn same_seed_same_sequence() {
+    let mut a = Rng::new(42);
+    let mut b = Rng::new(42);
+    let first = (0..8).map(|_| a.next_u64()).collect::<Vec<_>>();
+    assert_eq!(first, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+    let mut c = Rng::new(43);
+    assert_ne!(first, (0..8).map(|_| c.next_u64()).collect::<Vec<_>>());
+    // The reference output of SplitMix64 seeded with 0.
+    assert_eq!(Rng::new(0).next_u64(), 0xE220_A839_7B1D_CDAF);
+}
+
+#[test]
+fn below_stays_in_range() {
+    let mut rng = Rng::new(7);
+    let mut seen = [false; 6];
+    for _ in 0..1_000 {
+        seen[rng.below(6)] = true;
+    }
+    assert_eq!(seen, [true; 6]);
+    assert_eq!(rng.below(1), 0);
+}
+
+#[test]
+fn shuffle_keeps_every_item() {
+    let mut items = (0..20).collect::<Vec<_>>();
+    Rng::new(1).shuffle(&mut items);
+    assert_ne!(items, (0..20).collect::<Vec<_>>());
+    items.sort_unstable();
+    assert_eq!(items, (0..20).collect::<Vec<_>>());
+}
@@ -0,0 +1,698 @@
+use super::board::Board;
+use super::game::{Game, Move, Square};
+
+/// How a square was worked out, simplest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    /// A count whose hidden neighbors are all mines, or all safe.
+    SingleCell,
+    /// One count's hidden neighbors contain another's, and the difference
+    /// settles the squares only the larger one sees.
+    Subset,
+    /// Every placement of mines that fits the counts and the number of mines
+    /// left agrees on the square.
+    Enumeration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Deduction {
+    pub row: usize,
+    pub col: usize,
+    pub mine: bool,
+    pub rule: Rule,
+}
+
+/// What to play next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hint {
+    /// A square proven safe.
+    Reveal(usize, usize),
+    /// A square proven to be a mine, and not flagged yet.
+    Flag(usize, usize),
+    /// Nothing is certain: the square least likely to be a mine.
+    Guess {
+        row: usize,
+        col: usize,
+        mine_probability: f64,
+    },
+}
+
+impl Hint {
+    pub fn to_move(&self) -> Move {
+        match *self {
+            Self::Reveal(row, col) | Self::Guess { row, col, .. } => Move::Reveal(row, col),
+            Self::Flag(row, col) => Move::Flag(row, col),
+        }
+    }
+}
+
+/// Everything the solver could work out about a position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    width: usize,
+    deductions: Vec<Deduction>,
+    probabilities: Vec<Option<f64>>,
+    flagged: Vec<bool>,
+}
+
+impl Solution {
+    /// Squares proven safe or mines, in the order they were found.
+    pub fn deductions(&self) -> &[Deduction] {
+        &self.deductions
+    }
+
+    pub fn safe(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.proven(false)
+    }
+
+    pub fn mines(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.proven(true)
+    }
+
+    /// Chance that `(row, col)` is a mine, with every placement of the
+    /// remaining mines that fits the position equally likely. `None` for
+    /// squares that are already revealed.
+    pub fn probability(&self, row: usize, col: usize) -> Option<f64> {
+        assert!(col < self.width, "({row}, {col}) is off the board");
+        self.probabilities[row * self.width + col]
+    }
+
+    /// A proven safe square if there is one, then a proven mine that isn't
+    /// flagged, and otherwise the best guess. `None` once nothing is hidden.
+    pub fn hint(&self) -> Option<Hint> {
+        if let Some((row, col)) = self.safe().find(|&square| !self.is_flagged(square)) {
+            return Some(Hint::Reveal(row, col));
+        }
+        if let Some((row, col)) = self.mines().find(|&square| !self.is_flagged(square)) {
+            return Some(Hint::Flag(row, col));
+        }
+        self.probabilities
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| !self.flagged[index])
+            .filter_map(|(index, probability)| Some((index, (*probability)?)))
+            .filter(|&(_, probability)| probability < 1.0)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, mine_probability)| Hint::Guess {
+                row: index / self.width,
+                col: index % self.width,
+                mine_probability,
+            })
+    }
+
+    fn proven(&self, mine: bool) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.deductions
+            .iter()
+            .filter(move |deduction| deduction.mine == mine)
+            .map(|deduction| (deduction.row, deduction.col))
+    }
+
+    fn is_flagged(&self, (row, col): (usize, usize)) -> bool {
+        self.flagged[row * self.width + col]
+    }
+}
+
+/// Solve what the player of `game` can see. The mines on the game's board
+/// are never looked at.
+pub fn solve(game: &Game) -> Option<Solution> {
+    solve_with(game, Rule::Enumeration)
+}
+
+/// [`solve`], settling squares only with `strongest` and simpler rules.
+pub fn solve_with(game: &Game, strongest: Rule) -> Option<Solution> {
+    let squares = (0..game.height())
+        .flat_map(|row| (0..game.width()).map(move |col| game.square(row, col)))
+        .collect::<Vec<_>>();
+    solve_view_with(game.board(), &squares, game.mine_count(), strongest)
+}
+
+/// Solve a position given square by square, row after row, on a board of
+/// `geometry`'s size, neighborhood and topology with `mines` mines in all.
+///
+/// Flags are not trusted: a flagged square is as unknown as a hidden one.
+/// Returns `None` if no placement of mines fits the position.
+///
+/// Propagation settles what it can cheaply, then what is left of the
+/// frontier is enumerated exactly, each independent part on its own. That
+/// last step is exponential in the size of the largest part.
+pub fn solve_view(geometry: &Board, squares: &[Square], mines: usize) -> Option<Solution> {
+    solve_view_with(geometry, squares, mines, Rule::Enumeration)
+}
+
+/// [`solve_view`], settling squares only with `strongest` and simpler rules.
+/// The probabilities are exact all the same, so some may be 0 or 1 without a
+/// matching deduction.
+pub fn solve_view_with(
+    geometry: &Board,
+    squares: &[Square],
+    mines: usize,
+    strongest: Rule,
+) -> Option<Solution> {
+    let (width, height) = (geometry.width(), geometry.height());
+    assert_eq!(squares.len(), width * height, "squares don't fit the board");
+    let neighbors = (0..squares.len())
+        .map(|index| {
+            geometry
+                .neighbors(index / width, index % width)
+                .into_iter()
+                .map(|(row, col)| row * width + col)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut solver = Solver {
+        width,
+        squares,
+        neighbors: &neighbors,
+        known: squares
+            .iter()
+            .map(|&square| (square == Square::Exploded).then_some(true))
+            .collect(),
+        deductions: Vec::new(),
+        strongest,
+    };
+    solver.propagate()?;
+    let probabilities = solver.enumerate(mines)?;
+
+    Some(Solution {
+        width,
+        deductions: solver.deductions,
+        probabilities,
+        flagged: squares
+            .iter()
+            .map(|&square| square == Square::Flagged)
+            .collect(),
+    })
+}
+
+/// The hidden neighbors of a revealed square hold this many mines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Constraint {
+    cells: Vec<usize>,
+    mines: usize,
+}
+
+struct Solver<'a> {
+    width: usize,
+    squares: &'a [Square],
+    neighbors: &'a [Vec<usize>],
+    /// Whether a square is a mine, once that is certain.
+    known: Vec<Option<bool>>,
+    deductions: Vec<Deduction>,
+    strongest: Rule,
+}
+
+impl Solver<'_> {
+    fn is_unknown(&self, index: usize) -> bool {
+        matches!(self.squares[index], Square::Hidden | Square::Flagged)
+            && self.known[index].is_none()
+    }
+
+    /// The counts, less what is already known, over the squares that
+    /// aren't. `None` on a count that can't be met.
+    fn constraints(&self) -> Option<Vec<Constraint>> {
+        let mut constraints = Vec::new();
+        for (index, &square) in self.squares.iter().enumerate() {
+            let Square::Revealed(count) = square else {
+                continue;
+            };
+            let neighbors = &self.neighbors[index];
+            let known_mines = neighbors
+                .iter()
+                .filter(|&&cell| self.known[cell] == Some(true))
+                .count();
+            let cells = neighbors
+                .iter()
+                .copied()
+                .filter(|&cell| self.is_unknown(cell))
+                .collect::<Vec<_>>();
+            let mines = count.checked_sub(known_mines)?;
+            if mines > cells.len() {
+                return None;
+            }
+            if !cells.is_empty() {
+                constraints.push(Constraint { cells, mines });
+            }
+        }
+        for constraint in &mut constraints {
+            constraint.cells.sort_unstable();
+        }
+        constraints.sort_unstable_by(|a, b| a.cells.cmp(&b.cells));
+        constraints.dedup_by(|a, b| a.cells == b.cells && a.mines == b.mines);
+        Some(constraints)
+    }
+
+    fn settle(&mut self, cell: usize, mine: bool, rule: Rule) -> bool {
+        if self.known[cell].is_some() {
+            return false;
+        }
+        self.known[cell] = Some(mine);
+        self.deductions.push(Deduction {
+            row: cell / self.width,
+            col: cell % self.width,
+            mine,
+            rule,
+        });
+        true
+    }
+
+    /// Apply the single-cell and, if allowed, the subset rule until neither
+    /// finds anything.
+    fn propagate(&mut self) -> Option<()> {
+        loop {
+            let constraints = self.constraints()?;
+            let mut changed = false;
+            for constraint in &constraints {
+                if constraint.mines == 0 || constraint.mines == constraint.cells.len() {
+                    let mine = constraint.mines > 0;
+                    for &cell in &constraint.cells {
+                        changed |= self.settle(cell, mine, Rule::SingleCell);
+                    }
+                }
+            }
+            if changed {
+                continue;
+            }
+            if self.strongest < Rule::Subset {
+                return Some(());
+            }
+            for small in &constraints {
+                for large in &constraints {
+                    if small.cells.len() >= large.cells.len()
+                        || !small.cells.iter().all(|cell| large.cells.contains(cell))
+                    {
+                        continue;
+                    }
+                    let rest = large
+                        .cells
+                        .iter()
+                        .filter(|cell| !small.cells.contains(cell))
+                        .copied()
+                        .collect::<Vec<_>>();
+                    let mines = large.mines.checked_sub(small.mines)?;
+                    if mines == 0 || mines == rest.len() {
+                        for cell in rest {
+                            changed |= self.settle(cell, mines > 0, Rule::Subset);
+                        }
+                    }
+                }
+            }
+            if !changed {
+                return Some(());
+            }
+        }
+    }
+
+    /// Mine probabilities for every square, from every placement of the
+    /// remaining mines that fits, settling squares all placements agree on.
+    fn enumerate(&mut self, mines: usize) -> Option<Vec<Option<f64>>> {
+        let constraints = self.constraints()?;
+        let known_mines = self
+            .known
+            .iter()
+            .filter(|&&known| known == Some(true))
+            .count();
+        let left = mines.checked_sub(known_mines)?;
+
+        let components = components(&constraints)
+            .into_iter()
+            .map(|(cells, constraints)| Component::enumerate(cells, &constraints))
+            .collect::<Vec<_>>();
+        let mut in_frontier = vec![false; self.squares.len()];
+        components
+            .iter()
+            .flat_map(|component| &component.cells)
+            .for_each(|&cell| in_frontier[cell] = true);
+        let interior = (0..self.squares.len())
+            .filter(|&cell| self.is_unknown(cell) && !in_frontier[cell])
+            .collect::<Vec<_>>();
+
+        // Placements of the interior mines, for every number of mines the
+        // frontier could hold, relative to the smallest such count so the
+        // numbers stay in range.
+        let frontier_max = components.iter().map(|c| c.cells.len()).sum::<usize>();
+        let fill = interior_weights(interior.len(), left, frontier_max);
+        let others = |skip: Option<usize>| {
+            components
+                .iter()
+                .enumerate()
+                .filter(|&(index, _)| Some(index) != skip)
+                .fold(vec![1.0], |dist, (_, component)| {
+                    convolve(&dist, &component.solutions)
+                })
+        };
+
+        let mut probabilities = self
+            .known
+            .iter()
+            .map(|known| known.map(|mine| if mine { 1.0 } else { 0.0 }))
+            .collect::<Vec<_>>();
+        let mut settled = Vec::new();
+
+        let (mut total, mut interior_mine, mut interior_safe) = (0.0, 0.0, 0.0);
+        for (frontier, &weight) in others(None).iter().enumerate() {
+            let Some(fill) = fill(frontier) else {
+                continue;
+            };
+            let interior_mines = left - frontier;
+            total += weight * fill;
+            interior_mine += weight * fill * interior_mines as f64;
+            interior_safe += weight * fill * (interior.len() - interior_mines) as f64;
+        }
+        if total == 0.0 {
+            return None;
+        }
+        for &cell in &interior {
+            probabilities[cell] = Some(interior_mine / (interior_mine + interior_safe));
+            settled.push((cell, interior_mine, interior_safe));
+        }
+
+        for (index, component) in components.iter().enumerate() {
+            let rest = others(Some(index));
+            for (position, &cell) in component.cells.iter().enumerate() {
+                let (mut mine, mut safe) = (0.0, 0.0);
+                for (own, &solutions) in component.solutions.iter().enumerate() {
+                    let with_mine = component.cell_mines[own][position];
+                    for (other, &weight) in rest.iter().enumerate() {
+                        let Some(fill) = fill(own + other) else {
+                            continue;
+                        };
+                        mine += with_mine * weight * fill;
+                        safe += (solutions - with_mine) * weight * fill;
+                    }
+                }
+                probabilities[cell] = Some(mine / (mine + safe));
+                settled.push((cell, mine, safe));
+            }
+        }
+
+        for (cell, mine, safe) in settled {
+            if safe == 0.0 || mine == 0.0 {
+                if self.strongest == Rule::Enumeration {
+                    self.settle(cell, safe == 0.0, Rule::Enumeration);
+                }
+                probabilities[cell] = Some(if safe == 0.0 { 1.0 } else { 0.0 });
+            }
+        }
+        for (index, square) in self.squares.iter().enumerate() {
+            if let Square::Revealed(_) = square {
+                probabilities[index] = None;
+            }
+        }
+        Some(probabilities)
+    }
+}
+
+/// The counts that share squares, directly or through other counts, with
+/// the squares they cover.
+fn components(constraints: &[Constraint]) -> Vec<(Vec<usize>, Vec<Constraint>)> {
+    let mut components: Vec<(Vec<usize>, Vec<Constraint>)> = Vec::new();
+    for constraint in constraints {
+        let (touching, mut rest): (Vec<_>, Vec<_>) = components
+            .into_iter()
+            .partition(|(cells, _)| constraint.cells.iter().any(|cell| cells.contains(cell)));
+        let mut merged = (constraint.cells.clone(), vec![constraint.clone()]);
+        for (cells, constraints) in touching {
+            merged.0.extend(cells);
+            merged.1.extend(constraints);
+        }
+        merged.0.sort_unstable();
+        merged.0.dedup();
+        rest.push(merged);
+        components = rest;
+    }
+    components
+}
+
+/// Every placement of mines on one independent part of the frontier.
+struct Component {
+    cells: Vec<usize>,
+    /// Placements by number of mines.
+    solutions: Vec<f64>,
+    /// `cell_mines[k][i]`: placements of `k` mines with a mine on `cells[i]`.
+    cell_mines: Vec<Vec<f64>>,
+}
+
+impl Component {
+    fn enumerate(cells: Vec<usize>, constraints: &[Constraint]) -> Self {
+        let positions = constraints
+            .iter()
+            .map(|constraint| {
+                constraint
+                    .cells
+                    .iter()
+                    .map(|cell| cells.binary_search(cell).unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut watched_by = vec![Vec::new(); cells.len()];
+        for (index, positions) in positions.iter().enumerate() {
+            positions
+                .iter()
+                .for_each(|&position| watched_by[position].push(index));
+        }
+
+        let mut component = Self {
+            solutions: vec![0.0; cells.len() + 1],
+            cell_mines: vec![vec![0.0; cells.len()]; cells.len() + 1],
+            cells,
+        };
+        let mut search = Search {
+            targets: constraints
+                .iter()
+                .map(|constraint| constraint.mines)
+                .collect(),
+            placed: vec![0; constraints.len()],
+            open: positions.iter().map(Vec::len).collect(),
+            watched_by,
+            mines: Vec::new(),
+        };
+        search.run(&mut component);
+        component
+    }
+}
+
+/// Depth-first assignment of mine or no mine to each square in turn,
+/// backing out as soon as a count is exceeded or can no longer be reached.
+struct Search {
+    targets: Vec<usize>,
+    placed: Vec<usize>,
+    open: Vec<usize>,
+    watched_by: Vec<Vec<usize>>,
+    mines: Vec<bool>,
+}
+
+impl Search {
+    fn run(&mut self, component: &mut Component) {
+        let position = self.mines.len();
+        if position == component.cells.len() {
+            let count = self.mines.iter().filter(|&&mine| mine).count();
+            component.solutions[count] += 1.0;
+            for (position, _) in self.mines.iter().enumerate().filter(|(_, &mine)| mine) {
+                component.cell_mines[count][position] += 1.0;
+            }
+            return;
+        }
+        for mine in [false, true] {
+            let fits = self.watched_by[position].iter().all(|&constraint| {
+                let placed = self.placed[constraint] + usize::from(mine);
+                placed <= self.targets[constraint]
+                    && placed + self.open[constraint] > self.targets[constraint]
+            });
+            if !fits {
+                continue;
+            }
+            for &constraint in &self.watched_by[position] {
+                self.placed[constraint] += usize::from(mine);
+                self.open[constraint] -= 1;
+            }
+            self.mines.push(mine);
+            self.run(component);
+            self.mines.pop();
+            for &constraint in &self.watched_by[position] {
+                self.placed[constraint] -= usize::from(mine);
+                self.open[constraint] += 1;
+            }
+        }
+    }
+}
+
+/// `a` and `b` as polynomials in the number of mines, multiplied.
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut product = vec![0.0; a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            product[i + j] += x * y;
+        }
+    }
+    product
+}
+
+/// For a frontier holding `f` of the `left` mines: the ways to place the rest
+/// on `interior` squares, as a multiple of the fewest ways over all `f` up to
+/// `frontier_max`. `None` when the rest don't fit.
+fn interior_weights(
+    interior: usize,
+    left: usize,
+    frontier_max: usize,
+) -> impl Fn(usize) -> Option<f64> {
+    // C(n, k) / C(n, lowest) for k from `lowest` up.
+    let lowest = left.saturating_sub(frontier_max);
+    let mut ratios = Vec::new();
+    let mut ratio = 1.0;
+    for k in lowest..=left.min(interior) {
+        if k > lowest {
+            ratio *= (interior - k + 1) as f64 / k as f64;
+        }
+        ratios.push(ratio);
+    }
+    move |frontier| {
+        let rest = left.checked_sub(frontier)?;
+        ratios.get(rest.checked_sub(lowest)?).copied()
+    }
+}
+
+/// A position from rows where `#` is hidden, `F` flagged, `*` an exploded
+/// mine, a blank a revealed square without neighboring mines and a digit
+/// any other revealed square.
+fn view(rows: &[&str]) -> (Board, Vec<Square>) {
+    let board = Board::new(rows.first().map_or(0, |row| row.len()), rows.len());
+    let squares = rows
+        .iter()
+        .flat_map(|row| row.chars())
+        .map(|square| match square {
+            '#' => Square::Hidden,
+            'F' => Square::Flagged,
+            '*' => Square::Exploded,
+            ' ' => Square::Revealed(0),
+            digit => Square::Revealed(digit.to_digit(10).unwrap() as usize),
+        })
+        .collect();
+    (board, squares)
+}
+
+fn deduced(solution: &Solution) -> Vec<(usize, usize, bool, Rule)> {
+    let mut deduced = solution
+        .deductions()
+        .iter()
+        .map(|d| (d.row, d.col, d.mine, d.rule))
+        .collect::<Vec<_>>();
+    deduced.sort_by_key(|&(row, col, ..)| (row, col));
+    deduced
+}
+
+#[test]
+fn single_cell_rule() {
+    let (board, squares) = view(&["1#"]);
+    let solution = solve_view(&board, &squares, 1).unwrap();
+    assert_eq!(deduced(&solution), [(0, 1, true, Rule::SingleCell)]);
+    assert_eq!(solution.hint(), Some(Hint::Flag(0, 1)));
+
+    let (board, squares) = view(&["  ", "##"]);
+    let solution = solve_view(&board, &squares, 0).unwrap();
+    assert_eq!(solution.safe().count(), 2);
+    assert_eq!(solution.hint(), Some(Hint::Reveal(1, 0)));
+}
+
+#[test]
+fn subset_rule_solves_one_two_one() {
+    let (board, squares) = view(&["121", "###"]);
+    let solution = solve_view(&board, &squares, 2).unwrap();
+    assert_eq!(
+        deduced(&solution),
+        [
+            (1, 0, true, Rule::Subset),
+            (1, 1, false, Rule::SingleCell),
+            (1, 2, true, Rule::Subset)
+        ]
+    );
+}
+
+#[test]
+fn probabilities_weigh_frontier_against_interior() {
+    let (board, squares) = view(&["1##", "###", "###"]);
+    let solution = solve_view(&board, &squares, 2).unwrap();
+    assert!(solution.deductions().is_empty());
+    for (row, col) in [(0, 1), (1, 0), (1, 1)] {
+        assert!((solution.probability(row, col).unwrap() - 1.0 / 3.0).abs() < 1e-12);
+    }
+    assert!((solution.probability(2, 2).unwrap() - 0.2).abs() < 1e-12);
+    assert_eq!(solution.probability(0, 0), None);
+    assert!(matches!(
+        solution.hint(),
+        Some(Hint::Guess { row: 0, col: 2, .. })
+    ));
+}
+
+#[test]
+fn mine_count_settles_the_interior() {
+    let (board, squares) = view(&["1##", "1##"]);
+    let solution = solve_view(&board, &squares, 1).unwrap();
+    assert_eq!(
+        deduced(&solution),
+        [
+            (0, 2, false, Rule::Enumeration),
+            (1, 2, false, Rule::Enumeration)
+        ]
+    );
+    assert_eq!(solution.probability(0, 1), Some(0.5));
+    assert_eq!(solution.probability(1, 2), Some(0.0));
+}
+
+#[test]
+fn flags_are_not_trusted() {
+    let (board, squares) = view(&["1F", "1#"]);
+    let solution = solve_view(&board, &squares, 1).unwrap();
+    assert!(solution.deductions().is_empty());
+    assert_eq!(solution.probability(0, 1), Some(0.5));
+    // The flagged square is never the guess.
+    assert!(matches!(
+        solution.hint(),
+        Some(Hint::Guess { row: 1, col: 1, .. })
+    ));
+}
+
+#[test]
+fn contradictions() {
+    let (board, squares) = view(&["2#"]);
+    assert_eq!(solve_view(&board, &squares, 1), None);
+    let (board, squares) = view(&["1#", "##"]);
+    assert_eq!(solve_view(&board, &squares, 0), None);
+    let (board, squares) = view(&["  "]);
+    assert_eq!(solve_view(&board, &squares, 1), None);
+}
+
+#[test]
+fn proven_hints_never_lose() {
+    for seed in 0..20 {
+        let mut game = Game::new(9, 9, 10, seed);
+        game.reveal(4, 4).unwrap();
+        while game.status() == super::game::Status::Playing {
+            let solution = solve(&game).unwrap();
+            for (row, col) in solution.safe() {
+                assert!(!game.board().is_mine(row, col));
+            }
+            for (row, col) in solution.mines() {
+                assert!(game.board().is_mine(row, col));
+            }
+            let hint = solution.hint().unwrap();
+            let status = game.play(hint.to_move()).unwrap();
+            if let Hint::Reveal(..) = hint {
+                assert_ne!(status, super::game::Status::Lost);
+            }
+        }
+    }
+}
+
+#[test]
+fn weaker_rules_settle_less() {
+    let (board, squares) = view(&["121", "###"]);
+    let solution = solve_view_with(&board, &squares, 2, Rule::SingleCell).unwrap();
+    assert!(solution.deductions().is_empty());
+    assert_eq!(solution.probability(1, 1), Some(0.0));
+
+    let (board, squares) = view(&["1##", "1##"]);
+    let solution = solve_view_with(&board, &squares, 1, Rule::Subset).unwrap();
+    assert!(solution.deductions().is_empty());
+    assert_eq!(solution.probability(0, 2), Some(0.0));
+}
@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use super::options::CountOptions;
+
+/// Languages with a bundled reference distribution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    English,
+    French,
+    German,
+    Spanish,
+    Portuguese,
+    Italian,
+    Dutch,
+    Swedish,
+    Danish,
+    Finnish,
+    Polish,
+    Turkish,
+}
+
+impl Language {
+    pub const VALUES: [Self; 12] = [
+        Self::English,
+        Self::French,
+        Self::German,
+        Self::Spanish,
+        Self::Portuguese,
+        Self::Italian,
+        Self::Dutch,
+        Self::Swedish,
+        Self::Danish,
+        Self::Finnish,
+        Self::Polish,
+        Self::Turkish,
+    ];
+
+    /// Percentages of `a` to `z` in typical text, after the table in
+    /// Wikipedia's "Letter frequency" article. Accented letters are left out
+    /// of the table, so rows don't quite add up to 100 and text should be
+    /// counted with diacritics stripped before comparing.
+    // Swedish `k` happens to be 3.140.
+    #[allow(clippy::approx_constant)]
+    pub fn reference(&self) -> &'static [f64; 26] {
+        match self {
+            #[rustfmt::skip]
+            Self::English => &[
+                8.167, 1.492, 2.782, 4.253, 12.702, 2.228, 2.015, 6.094, 6.966, 0.153, 0.772, 4.025, 2.406,
+                6.749, 7.507, 1.929, 0.095, 5.987, 6.327, 9.056, 2.758, 0.978, 2.360, 0.150, 1.974, 0.074,
+            ],
+            #[rustfmt::skip]
+            Self::French => &[
+                7.636, 0.901, 3.260, 3.669, 14.715, 1.066, 0.866, 0.737, 7.529, 0.613, 0.049, 5.456, 2.968,
+                7.095, 5.796, 2.521, 1.362, 6.693, 7.948, 7.244, 6.311, 1.838, 0.074, 0.427, 0.128, 0.326,
+            ],
+            #[rustfmt::skip]
+            Self::German => &[
+                6.516, 1.886, 2.732, 5.076, 16.396, 1.656, 3.009, 4.577, 6.550, 0.268, 1.417, 3.437, 2.534,
+                9.776, 2.594, 0.670, 0.018, 7.003, 7.270, 6.154, 4.166, 0.846, 1.921, 0.034, 0.039, 1.134,
+            ],
+            #[rustfmt::skip]
+            Self::Spanish => &[
+                11.525, 2.215, 4.019, 5.010, 12.181, 0.692, 1.768, 0.703, 6.247, 0.493, 0.011, 4.967, 3.157,
+                6.712, 8.683, 2.510, 0.877, 6.871, 7.977, 4.632, 2.927, 1.138, 0.017, 0.215, 1.008, 0.467,
+            ],
+            #[rustfmt::skip]
+            Self::Portuguese => &[
+                14.634, 1.043, 3.882, 4.992, 12.570, 1.023, 1.303, 0.781, 6.186, 0.397, 0.015, 2.779, 4.738,
+                4.446, 9.735, 2.523, 1.204, 6.530, 6.805, 4.336, 3.639, 1.575, 0.037, 0.253, 0.006, 0.470,
+            ],
+            #[rustfmt::skip]
+            Self::Italian => &[
+                11.745, 0.927, 4.501, 3.736, 11.792, 1.153, 1.644, 0.636, 10.143, 0.011, 0.009, 6.510, 2.512,
+                6.883, 9.832, 3.056, 0.505, 6.367, 4.981, 5.623, 3.011, 2.097, 0.033, 0.003, 0.020, 1.181,
+            ],
+            #[rustfmt::skip]
+            Self::Dutch => &[
+                7.486, 1.584, 1.242, 5.933, 17.324, 0.805, 3.403, 2.380, 6.499, 1.461, 2.248, 3.568, 2.213,
+                10.032, 6.063, 1.570, 0.009, 6.411, 3.730, 6.790, 1.990, 2.850, 1.520, 0.036, 0.035, 1.390,
+            ],
+            #[rustfmt::skip]
+            Self::Swedish => &[
+                9.383, 1.535, 1.486, 4.702, 10.149, 2.027, 2.862, 2.090, 5.817, 0.614, 3.140, 5.275, 3.471,
+                8.542, 4.482, 1.839, 0.020, 8.431, 6.590, 7.691, 1.919, 2.415, 0.142, 0.159, 0.708, 0.070,
+            ],
+            #[rustfmt::skip]
+            Self::Danish => &[
+                6.025, 2.000, 0.565, 5.858, 15.453, 2.406, 4.077, 1.621, 6.000, 0.730, 3.395, 5.229, 3.237,
+                7.240, 4.636, 1.756, 0.007, 8.956, 5.805, 6.862, 1.979, 2.332, 0.069, 0.028, 0.698, 0.034,
+            ],
+            #[rustfmt::skip]
+            Self::Finnish => &[
+                12.217, 0.281, 0.281, 1.043, 7.968, 0.194, 0.392, 1.851, 10.817, 2.042, 4.973, 5.761, 3.202,
+                8.826, 5.614, 1.842, 0.013, 2.872, 7.862, 8.750, 5.008, 2.250, 0.094, 0.031, 1.745, 0.051,
+            ],
+            #[rustfmt::skip]
+            Self::Polish => &[
+                10.503, 1.740, 3.895, 3.725, 7.352, 0.143, 1.731, 1.015, 8.328, 1.836, 2.753, 2.564, 2.515,
+                6.237, 6.667, 2.445, 0.000, 5.243, 5.224, 2.475, 2.062, 0.012, 5.813, 0.004, 3.206, 4.852,
+            ],
+            #[rustfmt::skip]
+            Self::Turkish => &[
+                12.920, 2.844, 1.463, 5.206, 9.912, 0.461, 1.253, 1.212, 8.600, 0.034, 5.683, 5.922, 3.752,
+                7.487, 2.476, 0.886, 0.000, 6.722, 3.014, 3.314, 3.235, 0.959, 0.000, 0.000, 3.336, 1.500,
+            ],
+        }
+    }
+
+    /// Share of `a` to `z` in typical text, summing to one.
+    pub fn distribution(&self) -> [f64; 26] {
+        let reference = self.reference();
+        let total = reference.iter().sum::<f64>();
+        reference.map(|percent| percent / total)
+    }
+
+    /// Chance that two letters picked at random are the same.
+    pub fn index_of_coincidence(&self) -> f64 {
+        self.distribution().iter().map(|p| p * p).sum()
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Counts of `a` to `z`, ignoring every other key.
+pub fn letter_counts(freqs: &HashMap<char, usize>) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for (&ch, &count) in freqs {
+        if ch.is_ascii_lowercase() {
+            counts[(ch as u8 - b'a') as usize] += count;
+        }
+    }
+    counts
+}
+
+/// Pearson's chi-squared statistic of the observed letters against the
+/// language. Lower is closer; letters the reference never expects are given
+/// a tiny share so they are penalized instead of dividing by zero. Infinite
+/// when there are no letters to compare.
+pub fn chi_squared(freqs: &HashMap<char, usize>, language: Language) -> f64 {
+    chi_squared_counts(&letter_counts(freqs), &language.distribution())
+}
+
+/// Cosine of the angle between observed and reference letter vectors. One
+/// is identical proportions, zero is nothing in common.
+pub fn cosine_similarity(freqs: &HashMap<char, usize>, language: Language) -> f64 {
+    let counts = letter_counts(freqs);
+    let reference = language.distribution();
+    let dot = counts
+        .iter()
+        .zip(reference.iter())
+        .map(|(&count, p)| count as f64 * p)
+        .sum::<f64>();
+    let norm = |values: &mut dyn Iterator<Item = f64>| values.map(|v| v * v).sum::<f64>().sqrt();
+    let observed = norm(&mut counts.iter().map(|&count| count as f64));
+    let expected = norm(&mut reference.iter().copied());
+    if observed == 0.0 {
+        0.0
+    } else {
+        dot / (observed * expected)
+    }
+}
+
+/// Every bundled language with its chi-squared score, best match first.
+/// Empty when there are no letters to go on.
+pub fn identify_language(freqs: &HashMap<char, usize>) -> Vec<(Language, f64)> {
+    if letter_counts(freqs).iter().all(|&count| count == 0) {
+        return Vec::new();
+    }
+    let mut scores = Language::VALUES
+        .iter()
+        .map(|&language| (language, chi_squared(freqs, language)))
+        .collect::<Vec<_>>();
+    scores.sort_by(|a, b| a.1.total_cmp(&b.1));
+    scores
+}
+
+/// Chance that two letters of the text picked at random are the same, over
+/// `a` to `z`. About 0.066 for English prose and 0.038 for uniformly random
+/// letters.
+pub fn index_of_coincidence(freqs: &HashMap<char, usize>) -> f64 {
+    index_of_coincidence_counts(&letter_counts(freqs))
+}
+
+/// Caesar shift that most likely produced `ciphertext` from text in
+/// `language`; decrypt by shifting back. Zero if it has no letters.
+pub fn caesar_shift(ciphertext: &str, language: Language) -> u8 {
+    best_shift(&ascii_counts(ciphertext.chars()), &language.distribution())
+}
+
+/// Shift every ASCII letter of `text` forward by `shift`, keeping case.
+pub fn caesar_encrypt(text: &str, shift: u8) -> String {
+    vigenere(text, &[shift % 26])
+}
+
+pub fn caesar_decrypt(text: &str, shift: u8) -> String {
+    vigenere(text, &[(26 - shift % 26) % 26])
+}
+
+/// Most likely Vigenère key length, up to `max_len`.
+///
+/// Splitting the letters into columns with the right period leaves every
+/// column a plain Caesar cipher, whose index of coincidence is that of the
+/// language. Multiples of the period score just as well, so the shortest
+/// length within a few percent of the best score wins.
+pub fn vigenere_key_length(ciphertext: &str, max_len: usize) -> usize {
+    let letters = ascii_letters(ciphertext).collect::<Vec<_>>();
+    let max_len = max_len.clamp(1, letters.len().max(1));
+    let scores = (1..=max_len)
+        .map(|len| {
+            let total = (0..len)
+                .map(|column| {
+                    let column = letters.iter().skip(column).step_by(len).copied();
+                    index_of_coincidence_counts(&ascii_counts(column))
+                })
+                .sum::<f64>();
+            (len, total / len as f64)
+        })
+        .collect::<Vec<_>>();
+    let best = scores.iter().map(|&(_, score)| score).fold(0.0, f64::max);
+    scores
+        .iter()
+        .find(|&&(_, score)| score >= best * 0.9)
+        .map_or(1, |&(len, _)| len)
+}
+
+/// Most likely Vigenère key, as lowercase letters, for a key of at most
+/// `max_len` letters.
+pub fn vigenere_key(ciphertext: &str, language: Language, max_len: usize) -> String {
+    let len = vigenere_key_length(ciphertext, max_len);
+    let letters = ascii_letters(ciphertext).collect::<Vec<_>>();
+    let reference = language.distribution();
+    (0..len)
+        .map(|column| {
+            let column = letters.iter().skip(column).step_by(len).copied();
+            (b'a' + best_shift(&ascii_counts(column), &reference)) as char
+        })
+        .collect()
+}
+
+pub fn vigenere_encrypt(text: &str, key: &str) -> String {
+    vigenere(text, &key_shifts(key, false))
+}
+
+pub fn vigenere_decrypt(text: &str, key: &str) -> String {
+    vigenere(text, &key_shifts(key, true))
+}
+
+fn key_shifts(key: &str, inverse: bool) -> Vec<u8> {
+    ascii_letters(key)
+        .map(|ch| ch as u8 - b'a')
+        .map(|shift| if inverse { (26 - shift) % 26 } else { shift })
+        .collect()
+}
+
+/// Shift ASCII letters by the key shifts in turn. Anything else is copied
+/// and doesn't use up a key letter.
+fn vigenere(text: &str, shifts: &[u8]) -> String {
+    if shifts.is_empty() {
+        return text.to_owned();
+    }
+    let mut shifts = shifts.iter().cycle();
+    text.chars()
+        .map(|ch| {
+            let base = match ch {
+                'a'..='z' => b'a',
+                'A'..='Z' => b'A',
+                _ => return ch,
+            };
+            let shift = shifts.next().unwrap();
+            (base + (ch as u8 - base + shift) % 26) as char
+        })
+        .collect()
+}
+
+fn ascii_letters(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|ch| ch.to_ascii_lowercase())
+}
+
+fn ascii_counts<I: Iterator<Item = char>>(letters: I) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for ch in letters {
+        if ch.is_ascii_alphabetic() {
+            counts[(ch.to_ascii_lowercase() as u8 - b'a') as usize] += 1;
+        }
+    }
+    counts
+}
+
+/// The shift that turns `counts` back into the closest match to `reference`.
+fn best_shift(counts: &[usize; 26], reference: &[f64; 26]) -> u8 {
+    (0..26u8)
+        .map(|shift| {
+            let mut unshifted = *counts;
+            unshifted.rotate_left(shift as usize);
+            (shift, chi_squared_counts(&unshifted, reference))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(0, |(shift, _)| shift)
+}
+
+fn chi_squared_counts(counts: &[usize; 26], reference: &[f64; 26]) -> f64 {
+    let total = counts.iter().sum::<usize>() as f64;
+    if total == 0.0 {
+        return f64::INFINITY;
+    }
+    counts
+        .iter()
+        .zip(reference.iter())
+        .map(|(&count, &p)| {
+            let expected = total * p.max(1e-4);
+            (count as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+fn index_of_coincidence_counts(counts: &[usize; 26]) -> f64 {
+    let total = counts.iter().sum::<usize>();
+    if total < 2 {
+        return 0.0;
+    }
+    let pairs = counts
+        .iter()
+        .map(|&n| n * n.saturating_sub(1))
+        .sum::<usize>();
+    pairs as f64 / (total * (total - 1)) as f64
+}
+
+/// Letter counts of `input` prepared for comparison with the references:
+/// lowercase with diacritics stripped.
+pub fn analysis_frequency(input: &[&str], worker_count: usize) -> HashMap<char, usize> {
+    let options = CountOptions::new().strip_diacritics(true);
+    super::options::frequency_with_options(input, worker_count, &options)
+        .into_iter()
+        .filter_map(|(unit, count)| Some((unit.chars().next()?, count)))
+        .collect()
+}
+
+// Abraham Lincoln, the Gettysburg Address.
+const GETTYSBURG: &str = "Four score and seven years ago our fathers brought forth on this \
+    continent, a new nation, conceived in Liberty, and dedicated to the proposition that all men \
+    are created equal. Now we are engaged in a great civil war, testing whether that nation, or \
+    any nation so conceived and so dedicated, can long endure. We are met on a great battle-field \
+    of that war. We have come to dedicate a portion of that field, as a final resting place for \
+    those who here gave their lives that that nation might live. It is altogether fitting and \
+    proper that we should do this. But, in a larger sense, we can not dedicate -- we can not \
+    consecrate -- we can not hallow -- this ground. The brave men, living and dead, who \
+    struggled here, have consecrated it, far above our poor power to add or detract. The world \
+    will little note, nor long remember what we say here, but it can never forget what they did \
+    here. It is for us the living, rather, to be dedicated here to the unfinished work which they \
+    who fought here have thus far so nobly advanced. It is rather for us to be here dedicated to \
+    the great task remaining before us -- that from these honored dead we take increased devotion \
+    to that cause for which they gave the last full measure of devotion -- that we here highly \
+    resolve that these dead shall not have died in vain -- that this nation, under God, shall \
+    have a new birth of freedom -- and that government of the people, by the people, for the \
+    people, shall not perish from the earth.";
+
+#[test]
+fn english_is_identified() {
+    let freqs = analysis_frequency(&[GETTYSBURG], 2);
+    assert_eq!(identify_language(&freqs)[0].0, Language::English);
+    assert!(cosine_similarity(&freqs, Language::English) > 0.95);
+    assert!(
+        cosine_similarity(&freqs, Language::English) > cosine_similarity(&freqs, Language::Finnish)
+    );
+}
+
+#[test]
+fn no_letters_to_analyse() {
+    for input in ["", "1863 - 1865!"] {
+        let freqs = analysis_frequency(&[input], 2);
+        assert_eq!(chi_squared(&freqs, Language::English), f64::INFINITY);
+        assert!(identify_language(&freqs).is_empty());
+        assert_eq!(caesar_shift(input, Language::English), 0);
+    }
+}
+
+#[test]
+fn index_of_coincidence_of_english() {
+    let freqs = analysis_frequency(&[GETTYSBURG], 2);
+    let ioc = index_of_coincidence(&freqs);
+    assert!((ioc - Language::English.index_of_coincidence()).abs() < 0.01);
+    assert_eq!(index_of_coincidence(&HashMap::from([('a', 1)])), 0.0);
+}
+
+#[test]
+fn references_are_percentages() {
+    for language in Language::VALUES {
+        let total = language.reference().iter().sum::<f64>();
+        // Accented letters are missing, so most rows fall a little short.
+        assert!((90.0..100.01).contains(&total), "{language}: {total}");
+    }
+}
+
+#[test]
+fn caesar_shift_is_recovered() {
+    let ciphertext = caesar_encrypt(GETTYSBURG, 7);
+    assert_eq!(caesar_shift(&ciphertext, Language::English), 7);
+    assert_eq!(caesar_decrypt(&ciphertext, 7), GETTYSBURG);
+}
+
+#[test]
+fn vigenere_key_is_recovered() {
+    let ciphertext = vigenere_encrypt(GETTYSBURG, "Lemon");
+    assert_eq!(vigenere_key_length(&ciphertext, 12), 5);
+    assert_eq!(vigenere_key(&ciphertext, Language::English, 12), "lemon");
+    assert_eq!(vigenere_decrypt(&ciphertext, "lemon"), GETTYSBURG);
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use super::counter::LocalCounter;
+use super::{map_reduce, process_line};
+
+const WORD: usize = size_of::<usize>();
+const HIGH_BITS: usize = usize::from_ne_bytes([0x80; WORD]);
+
+/// Byte histograms interleaved so that a run of the same letter doesn't make
+/// every increment wait for the previous one to land.
+const LANES: usize = 4;
+
+/// Counts letters like [`LocalCounter`], but takes every ASCII run of a line
+/// a byte at a time: a histogram of raw bytes, with upper and lower case only
+/// folded together once the counting is done.
+///
+/// ASCII runs are found a machine word at a time, which the compiler turns
+/// into vector loads where it can; `std::simd` would do the same by hand but
+/// is still nightly only. Whatever isn't ASCII goes through the regular
+/// Unicode path, and since UTF-8 never uses ASCII bytes inside a multi-byte
+/// char, cutting a line at its ASCII runs never splits one. A line with a `Σ`
+/// goes through it whole, since how that lowercases depends on the letters
+/// either side of it, ASCII or not.
+#[derive(Debug, Clone)]
+pub struct AsciiCounter {
+    bytes: [[usize; 256]; LANES],
+    rest: LocalCounter,
+}
+
+impl Default for AsciiCounter {
+    fn default() -> Self {
+        Self {
+            bytes: [[0; 256]; LANES],
+            rest: LocalCounter::new(),
+        }
+    }
+}
+
+impl AsciiCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_line(&mut self, line: &str) {
+        if line.contains('Σ') {
+            return process_line(|ch| self.rest.add(ch), line);
+        }
+        let mut rest = line;
+        while !rest.is_empty() {
+            let ascii = ascii_prefix_len(rest.as_bytes());
+            self.add_ascii(&rest.as_bytes()[..ascii]);
+            rest = &rest[ascii..];
+
+            let other = rest
+                .bytes()
+                .position(|byte| byte.is_ascii())
+                .unwrap_or(rest.len());
+            process_line(|ch| self.rest.add(ch), &rest[..other]);
+            rest = &rest[other..];
+        }
+    }
+
+    fn add_ascii(&mut self, bytes: &[u8]) {
+        let mut quads = bytes.chunks_exact(LANES);
+        for quad in &mut quads {
+            for (lane, &byte) in quad.iter().enumerate() {
+                self.bytes[lane][byte as usize] += 1;
+            }
+        }
+        for &byte in quads.remainder() {
+            self.bytes[0][byte as usize] += 1;
+        }
+    }
+
+    pub fn into_local(self) -> LocalCounter {
+        let mut counter = self.rest;
+        for lower in b'a'..=b'z' {
+            let upper = lower.to_ascii_uppercase();
+            let count = self
+                .bytes
+                .iter()
+                .map(|lane| lane[lower as usize] + lane[upper as usize])
+                .sum();
+            if count > 0 {
+                counter.add_count(char::from(lower), count);
+            }
+        }
+        counter
+    }
+
+    pub fn into_map(self) -> HashMap<char, usize> {
+        self.into_local().into_map()
+    }
+}
+
+/// Length of the longest all-ASCII prefix of `bytes`, checked a word at a
+/// time until a word has a high bit set.
+pub fn ascii_prefix_len(bytes: &[u8]) -> usize {
+    let mut words = bytes.chunks_exact(WORD);
+    let mut len = 0;
+    for word in &mut words {
+        if usize::from_ne_bytes(word.try_into().unwrap()) & HIGH_BITS != 0 {
+            break;
+        }
+        len += WORD;
+    }
+    len + bytes[len..]
+        .iter()
+        .position(|byte| !byte.is_ascii())
+        .unwrap_or(bytes.len() - len)
+}
+
+/// Count letters on scoped threads like [`Strategy::Scoped`](super::Strategy),
+/// with the ASCII fast path of [`AsciiCounter`].
+pub fn frequency(input: &[&str], worker_count: usize) -> HashMap<char, usize> {
+    map_reduce(input, worker_count, |lines| {
+        let mut counter = AsciiCounter::new();
+        lines.iter().for_each(|line| counter.add_line(line));
+        counter.into_map()
+    })
+}
+
+#[test]
+fn ascii_prefix_stops_at_first_non_ascii_byte() {
+    assert_eq!(ascii_prefix_len(b""), 0);
+    assert_eq!(ascii_prefix_len(b"abc"), 3);
+    assert_eq!(ascii_prefix_len("heb ik altijd geëerd.".as_bytes()), 16);
+    assert_eq!(ascii_prefix_len("ö".as_bytes()), 0);
+    let long = "a".repeat(3 * WORD + 1);
+    assert_eq!(ascii_prefix_len(long.as_bytes()), long.len());
+}
+
+#[test]
+fn mixed_lines_match_unicode_path() {
+    let lines = [
+        "Freude schöner Götterfunken,",
+        "O'er the RAMPARTS we watched",
+        "Ωmega and the \u{212A}elvin sign",
+        "e\u{301}tude ÉTUDE",
+        "",
+        "1, 2, 3!",
+        "ΟΔΟΣ",
+        "ΟΔΟΣa ΟΔΟΣ1 aΣ a\u{301}Σ",
+        "ΣΟΦΟΣ ΟΔΟΣ",
+    ];
+    for worker_count in 0..4 {
+        assert_eq!(
+            frequency(&lines, worker_count),
+            super::calc_frequency(&lines)
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Letters below this code point (ASCII and Latin-1) are counted in a plain
+/// array slot instead of a map entry.
+pub const TABLE_LEN: usize = 256;
+
+const SHARDS: usize = 16;
+
+/// Counter owned by a single thread. Nothing is shared, so nothing is locked.
+#[derive(Debug, Clone)]
+pub struct LocalCounter {
+    table: [usize; TABLE_LEN],
+    rest: HashMap<char, usize>,
+}
+
+impl Default for LocalCounter {
+    fn default() -> Self {
+        Self {
+            table: [0; TABLE_LEN],
+            rest: HashMap::new(),
+        }
+    }
+}
+
+impl LocalCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, ch: char) {
+        self.add_count(ch, 1);
+    }
+
+    pub fn add_count(&mut self, ch: char, count: usize) {
+        match self.table.get_mut(ch as usize) {
+            Some(slot) => *slot += count,
+            None => *self.rest.entry(ch).or_insert(0) += count,
+        }
+    }
+
+    /// Add every count from `self` into `store`.
+    pub fn merge_into(self, store: &mut HashMap<char, usize>) {
+        for (ch, count) in table_entries(&self.table) {
+            *store.entry(ch).or_insert(0) += count;
+        }
+        for (ch, count) in self.rest {
+            *store.entry(ch).or_insert(0) += count;
+        }
+    }
+
+    pub fn into_map(self) -> HashMap<char, usize> {
+        let mut store = HashMap::new();
+        self.merge_into(&mut store);
+        store
+    }
+}
+
+/// Counter shared between threads.
+///
+/// Latin-1 letters go to an array of atomics, so the hot path is a single
+/// `fetch_add`. Everything else lands in one of several small maps picked by
+/// code point, which keeps two threads counting different scripts from
+/// fighting over the same lock.
+#[derive(Debug)]
+pub struct AtomicCounter {
+    table: [AtomicUsize; TABLE_LEN],
+    shards: [Mutex<HashMap<char, usize>>; SHARDS],
+}
+
+impl Default for AtomicCounter {
+    fn default() -> Self {
+        Self {
+            table: [const { AtomicUsize::new(0) }; TABLE_LEN],
+            shards: std::array::from_fn(|_| Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl AtomicCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, ch: char) {
+        match self.table.get(ch as usize) {
+            Some(count) => {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+            None => {
+                let mut shard = self.shard(ch).lock().unwrap();
+                *shard.entry(ch).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Flush a thread-local counter into the shared one.
+    pub fn absorb(&self, local: LocalCounter) {
+        for (ch, count) in table_entries(&local.table) {
+            self.table[ch as usize].fetch_add(count, Ordering::Relaxed);
+        }
+        for (ch, count) in local.rest {
+            let mut shard = self.shard(ch).lock().unwrap();
+            *shard.entry(ch).or_insert(0) += count;
+        }
+    }
+
+    pub fn into_map(self) -> HashMap<char, usize> {
+        let table = self.table.map(AtomicUsize::into_inner);
+        let mut store = table_entries(&table).collect::<HashMap<_, _>>();
+        for shard in self.shards {
+            store.extend(shard.into_inner().unwrap());
+        }
+        store
+    }
+
+    fn shard(&self, ch: char) -> &Mutex<HashMap<char, usize>> {
+        &self.shards[ch as usize % SHARDS]
+    }
+}
+
+fn table_entries(table: &[usize; TABLE_LEN]) -> impl Iterator<Item = (char, usize)> + '_ {
+    table
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count > 0)
+        .map(|(code, &count)| (char::from(code as u8), count))
+}
+
+#[test]
+fn local_counter_splits_table_and_map() {
+    let mut counter = LocalCounter::new();
+    "aäяaя".chars().for_each(|ch| counter.add(ch));
+
+    let store = counter.into_map();
+    assert_eq!(store.get(&'a'), Some(&2));
+    assert_eq!(store.get(&'ä'), Some(&1));
+    assert_eq!(store.get(&'я'), Some(&2));
+    assert_eq!(store.len(), 3);
+}
+
+#[test]
+fn atomic_counter_from_many_threads() {
+    let counter = AtomicCounter::new();
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                "abcλ".chars().for_each(|ch| counter.add(ch));
+                let mut local = LocalCounter::new();
+                "aλ".chars().for_each(|ch| local.add(ch));
+                counter.absorb(local);
+            });
+        }
+    });
+
+    let store = counter.into_map();
+    assert_eq!(store.get(&'a'), Some(&8));
+    assert_eq!(store.get(&'b'), Some(&4));
+    assert_eq!(store.get(&'λ'), Some(&8));
+}
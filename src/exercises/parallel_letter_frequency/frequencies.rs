@@ -0,0 +1,259 @@
+use std::collections::{hash_map, HashMap};
+use std::hash::Hash;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Deref};
+
+use super::ngram::top_k;
+
+/// Counts per key, as returned by the counting functions, with the merging
+/// and comparing they need built in.
+///
+/// Reads like the `HashMap` it wraps. Keys are never stored with a count of
+/// zero, so two `Frequencies` are equal exactly when they counted the same.
+#[derive(Debug, Clone)]
+pub struct Frequencies<K = char>(HashMap<K, usize>);
+
+impl<K> Default for Frequencies<K> {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<K: Eq + Hash> Frequencies<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn increment(&mut self, key: K) {
+        self.add_count(key, 1);
+    }
+
+    pub fn add_count(&mut self, key: K, count: usize) {
+        if count > 0 {
+            *self.0.entry(key).or_insert(0) += count;
+        }
+    }
+
+    /// Sum of all counts.
+    pub fn total(&self) -> usize {
+        self.0.values().sum()
+    }
+
+    /// Every count as a share of [`total`](Self::total), so corpora of
+    /// different sizes can be compared. Empty when nothing was counted.
+    pub fn relative(&self) -> HashMap<&K, f64> {
+        let total = self.total() as f64;
+        self.0
+            .iter()
+            .map(|(key, &count)| (key, count as f64 / total))
+            .collect()
+    }
+
+    pub fn into_inner(self) -> HashMap<K, usize> {
+        self.0
+    }
+}
+
+impl<K: Ord + Hash> Frequencies<K> {
+    /// Entries in key order.
+    pub fn sorted(&self) -> Vec<(&K, usize)> {
+        let mut entries = self
+            .0
+            .iter()
+            .map(|(key, &count)| (key, count))
+            .collect::<Vec<_>>();
+        entries.sort_unstable();
+        entries
+    }
+
+    /// The `k` most frequent entries, most frequent first and ties in key
+    /// order.
+    pub fn top_k(&self, k: usize) -> Vec<(&K, usize)> {
+        top_k(self.0.iter().map(|(key, &count)| (key, count)), k)
+    }
+
+    /// How much more often each key was counted here than in `other`, in
+    /// key order. Keys counted equally often in both are left out.
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Vec<(&'a K, isize)> {
+        self.changes(other, |ours, theirs| {
+            let change = ours as isize - theirs as isize;
+            (change != 0).then_some(change)
+        })
+    }
+
+    /// Like [`diff`](Self::diff), but between [`relative`](Self::relative)
+    /// frequencies.
+    pub fn relative_diff<'a>(&'a self, other: &'a Self) -> Vec<(&'a K, f64)> {
+        let (total, other_total) = (self.total() as f64, other.total() as f64);
+        self.changes(other, |ours, theirs| {
+            let ours = if ours == 0 { 0.0 } else { ours as f64 / total };
+            let theirs = if theirs == 0 {
+                0.0
+            } else {
+                theirs as f64 / other_total
+            };
+            (ours != theirs).then_some(ours - theirs)
+        })
+    }
+
+    fn changes<'a, T>(
+        &'a self,
+        other: &'a Self,
+        mut change: impl FnMut(usize, usize) -> Option<T>,
+    ) -> Vec<(&'a K, T)> {
+        let count = |store: &Self, key| store.0.get(key).copied().unwrap_or(0);
+        let mut keys = self
+            .0
+            .keys()
+            .chain(other.0.keys().filter(|key| !self.0.contains_key(*key)))
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.into_iter()
+            .filter_map(|key| Some((key, change(count(self, key), count(other, key))?)))
+            .collect()
+    }
+}
+
+impl<K> Deref for Frequencies<K> {
+    type Target = HashMap<K, usize>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<K: Eq + Hash> PartialEq for Frequencies<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K: Eq + Hash> Eq for Frequencies<K> {}
+
+impl<K: Eq + Hash> PartialEq<HashMap<K, usize>> for Frequencies<K> {
+    fn eq(&self, other: &HashMap<K, usize>) -> bool {
+        self.0 == *other
+    }
+}
+
+impl<K: Eq + Hash> From<HashMap<K, usize>> for Frequencies<K> {
+    fn from(mut store: HashMap<K, usize>) -> Self {
+        store.retain(|_, &mut count| count > 0);
+        Self(store)
+    }
+}
+
+impl<K> From<Frequencies<K>> for HashMap<K, usize> {
+    fn from(frequencies: Frequencies<K>) -> Self {
+        frequencies.0
+    }
+}
+
+impl<K: Eq + Hash> AddAssign for Frequencies<K> {
+    fn add_assign(&mut self, mut rhs: Self) {
+        // Fold the smaller map into the larger one.
+        if self.0.len() < rhs.0.len() {
+            std::mem::swap(self, &mut rhs);
+        }
+        for (key, count) in rhs.0 {
+            self.add_count(key, count);
+        }
+    }
+}
+
+impl<K: Eq + Hash> Add for Frequencies<K> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl<K: Eq + Hash> Sum for Frequencies<K> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(), Add::add)
+    }
+}
+
+/// Count every key.
+impl<K: Eq + Hash> FromIterator<K> for Frequencies<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut frequencies = Self::new();
+        iter.into_iter().for_each(|key| frequencies.increment(key));
+        frequencies
+    }
+}
+
+/// Add up `(key, count)` pairs; a key may come up more than once.
+impl<K: Eq + Hash> FromIterator<(K, usize)> for Frequencies<K> {
+    fn from_iter<I: IntoIterator<Item = (K, usize)>>(iter: I) -> Self {
+        let mut frequencies = Self::new();
+        iter.into_iter()
+            .for_each(|(key, count)| frequencies.add_count(key, count));
+        frequencies
+    }
+}
+
+impl<K> IntoIterator for Frequencies<K> {
+    type Item = (K, usize);
+    type IntoIter = hash_map::IntoIter<K, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, K> IntoIterator for &'a Frequencies<K> {
+    type Item = (&'a K, &'a usize);
+    type IntoIter = hash_map::Iter<'a, K, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[test]
+fn sum_of_worker_results() {
+    let stores = [
+        HashMap::from([('a', 2), ('b', 1)]),
+        HashMap::new(),
+        HashMap::from([('a', 1), ('c', 4), ('d', 0)]),
+    ];
+    let total = stores
+        .into_iter()
+        .map(Frequencies::from)
+        .sum::<Frequencies>();
+    assert_eq!(total, HashMap::from([('a', 3), ('b', 1), ('c', 4)]));
+    assert_eq!(total.total(), 8);
+}
+
+#[test]
+fn add_and_collect() {
+    let mut frequencies = "abca".chars().collect::<Frequencies>();
+    frequencies += [('c', 2), ('z', 0)].into_iter().collect();
+    let frequencies = frequencies + Frequencies::new();
+    assert_eq!(frequencies.sorted(), vec![(&'a', 2), (&'b', 1), (&'c', 3)]);
+    assert_eq!(frequencies.top_k(2), vec![(&'c', 3), (&'a', 2)]);
+    assert_eq!(frequencies.get(&'z'), None);
+}
+
+#[test]
+fn relative_frequencies() {
+    let frequencies = "aaab".chars().collect::<Frequencies>();
+    let relative = frequencies.relative();
+    assert_eq!(relative[&'a'], 0.75);
+    assert_eq!(relative[&'b'], 0.25);
+    assert!(Frequencies::<char>::new().relative().is_empty());
+}
+
+#[test]
+fn diff_between_corpora() {
+    let ours = "aabbc".chars().collect::<Frequencies>();
+    let theirs = "abbd".chars().collect::<Frequencies>();
+    assert_eq!(ours.diff(&theirs), vec![(&'a', 1), (&'c', 1), (&'d', -1)]);
+
+    let doubled = "aabbbbdd".chars().collect::<Frequencies>();
+    assert!(theirs.relative_diff(&doubled).is_empty());
+    assert_eq!(ours.relative_diff(&ours), vec![]);
+}
@@ -0,0 +1,429 @@
+use std::cmp;
+use std::collections::hash_map::HashMap;
+use std::hash::Hash;
+use std::iter::{repeat, repeat_with};
+use std::ops::Add;
+use std::slice::Chunks;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+pub mod analysis;
+pub mod ascii;
+pub mod counter;
+pub mod frequencies;
+pub mod ngram;
+pub mod options;
+#[cfg(feature = "rayon")]
+pub mod par_iter;
+pub mod partition;
+pub mod pool;
+pub mod stream;
+
+/// How [`frequency_with`] spreads the input over its workers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Count everything on the calling thread.
+    Sequential,
+    /// Deal lines round-robin to workers over channels.
+    Channels,
+    /// Share a single `Mutex<HashMap>` between workers.
+    Mutex,
+    /// Count chunks on scoped threads and merge their maps.
+    Scoped,
+    /// Share a map of `AtomicUsize` counters between workers, taking a write
+    /// lock only to add chars it hasn't seen yet.
+    Atomic,
+    /// Let workers pull lines from a shared cursor until the input runs out.
+    WorkStealing,
+    /// Bump a shared array of atomics for Latin-1 letters, no locks on the hot path.
+    SharedArray,
+    /// Count into a per-thread array and flush it into the shared one once.
+    LocalArray,
+    /// Like `Scoped`, but counts runs of ASCII bytewise instead of char by char.
+    Ascii,
+}
+
+impl Strategy {
+    pub const VALUES: [Self; 9] = [
+        Self::Sequential,
+        Self::Channels,
+        Self::Mutex,
+        Self::Scoped,
+        Self::Atomic,
+        Self::WorkStealing,
+        Self::SharedArray,
+        Self::LocalArray,
+        Self::Ascii,
+    ];
+}
+
+/// Count letters in `input` with the given strategy.
+///
+/// Every strategy returns the same map for the same input, so callers can
+/// switch between them without touching anything else.
+pub fn frequency_with(
+    input: &[&str],
+    worker_count: usize,
+    strategy: Strategy,
+) -> HashMap<char, usize> {
+    match strategy {
+        Strategy::Sequential => calc_frequency(input),
+        Strategy::Channels => frequency(input, worker_count),
+        Strategy::Mutex => mutex::frequency(input, worker_count),
+        Strategy::Scoped => scope::frequency(input, worker_count),
+        Strategy::Atomic => scope_atomic::frequency(input, worker_count),
+        Strategy::WorkStealing => work_stealing::frequency(input, worker_count),
+        Strategy::SharedArray => lock_free::frequency(input, worker_count),
+        Strategy::LocalArray => lock_free::frequency_local(input, worker_count),
+        Strategy::Ascii => ascii::frequency(input, worker_count),
+    }
+}
+
+/// [`frequency_with`], as [`Frequencies`](frequencies::Frequencies) to merge
+/// or compare with other results.
+pub fn frequencies_with(
+    input: &[&str],
+    worker_count: usize,
+    strategy: Strategy,
+) -> frequencies::Frequencies {
+    frequency_with(input, worker_count, strategy).into()
+}
+
+// pub mod channels {
+//     use std::cmp;
+//     use std::collections::HashMap;
+//     use std::iter::{self, repeat_with};
+//     use std::slice::Chunks;
+//     use std::sync::atomic::{AtomicUsize, Ordering};
+//     use std::sync::{mpsc, Arc, Mutex};
+//     use std::thread::{self, JoinHandle};
+    fn process_line<T, S>(processor: T, line: S)
+    where
+        T: FnMut(char),
+        S: AsRef<str>,
+    {
+        options::CountOptions::default().for_each_char(line.as_ref(), processor)
+    }
+    fn calc_frequency(lines: &[&str]) -> HashMap<char, usize> {
+        let mut store = HashMap::<char, usize>::new();
+
+        for line in lines {
+            process_line(
+                |ch| {
+                    *store.entry(ch).or_insert(0) += 1;
+                },
+                line,
+            );
+        }
+
+        store
+    }
+
+    fn merge<K, I>(stores: I) -> HashMap<K, usize>
+    where
+        K: Eq + Hash,
+        I: IntoIterator<Item = HashMap<K, usize>>,
+    {
+        stores
+            .into_iter()
+            .map(frequencies::Frequencies::from)
+            .sum::<frequencies::Frequencies<K>>()
+            .into_inner()
+    }
+
+    /// Count every chunk of `input` on its own scoped thread and merge the
+    /// results.
+    fn map_reduce<K, F>(input: &[&str], worker_count: usize, count: F) -> HashMap<K, usize>
+    where
+        K: Eq + Hash + Send,
+        F: Fn(&[&str]) -> HashMap<K, usize> + Sync,
+    {
+        let stores = thread::scope(|scope| {
+            let handles = split(input, worker_count)
+                .map(|chunk| scope.spawn(|| count(chunk)))
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        merge(stores)
+    }
+
+    fn split<'a, 'b>(input: &'a [&'b str], len: usize) -> impl Iterator<Item = &'a [&'b str]> {
+        partition::chunks(input, len)
+    }
+
+    pub fn frequency<'a>(input: &'a [&str], worker_count: usize) -> HashMap<char, usize> {
+        let worker_count = worker_count.clamp(1, input.len().max(1));
+
+        let (senders, receivers) = repeat_with(|| mpsc::channel::<&str>())
+            .take(worker_count)
+            .fold((vec![], vec![]), |mut acc, (sender, receiver)| {
+                acc.0.push(sender);
+                acc.1.push(receiver);
+                acc
+            });
+
+        // Workers have to be running before the first line is sent, otherwise
+        // every receiver is drained one after another.
+        let stores = thread::scope(|scope| {
+            let workers = receivers
+                .into_iter()
+                .map(|receiver| {
+                    scope.spawn(move || {
+                        let mut store = HashMap::<char, usize>::new();
+                        for line in receiver {
+                            process_line(
+                                |ch| {
+                                    *store.entry(ch).or_insert(0) += 1;
+                                },
+                                line,
+                            );
+                        }
+                        store
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            input.iter().enumerate().for_each(|(index, &line)| {
+                let sender = senders.get(index % worker_count).unwrap();
+                sender.send(line).unwrap();
+            });
+            drop(senders);
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        merge(stores)
+    }
+// }
+
+pub mod mutex {
+    use std::cmp;
+    use std::collections::HashMap;
+    use std::iter::{self, repeat_with};
+    use std::slice::Chunks;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread::{self, JoinHandle};
+
+    fn calc_frequency(lines: Vec<String>, store: Arc<Mutex<HashMap<char, usize>>>) {
+        for line in lines {
+            super::process_line(
+                |ch| {
+                    let store_guard = &mut store.lock().unwrap();
+                    *store_guard.entry(ch).or_insert(0) += 1;
+                },
+                line,
+            );
+        }
+    }
+
+    // fn demo() {
+    //     use std::cmp;
+
+    //     let x = 5;
+    //     let y = 6;
+
+    //     let z = max(&x, &y);
+
+    //     print!("{:#?}", x);
+    //     print!("{:#?}", z);
+
+    //     fn max<T: cmp::PartialOrd + Copy>(a: &T, b: &T) -> T {
+    //         if a > b {
+    //             *a
+    //         } else {
+    //             *b
+    //         }
+    //     }
+    // }
+
+    pub fn frequency(input: &[&str], worker_count: usize) -> HashMap<char, usize> {
+        let store = Mutex::new(HashMap::<char, usize>::new());
+        let store_rc = Arc::new(store);
+
+        // Spawn every worker before joining any, or they run one at a time.
+        let handles = super::split(input, worker_count)
+            .map(|chunk| chunk.iter().map(|line| line.to_string()).collect::<Vec<_>>())
+            .map(|chunk| {
+                let th_store = store_rc.clone();
+                thread::spawn(move || {
+                    calc_frequency(chunk, th_store);
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let hash_map = store_rc.lock().unwrap();
+        hash_map.clone()
+    }
+}
+pub mod scope {
+    use std::cmp;
+    use std::collections::HashMap;
+    use std::iter;
+    use std::slice::Chunks;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use super::{calc_frequency, map_reduce};
+
+    pub fn frequency(input: &[&str], worker_count: usize) -> HashMap<char, usize> {
+        map_reduce(input, worker_count, calc_frequency)
+    }
+}
+
+mod scope_atomic {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::RwLock;
+    use std::thread;
+
+    fn calc_frequency(lines: &[&str], store: &RwLock<HashMap<char, AtomicUsize>>) {
+        for &line in lines {
+            super::process_line(
+                |ch| {
+                    if let Some(count) = store.read().unwrap().get(&ch) {
+                        count.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    let mut store = store.write().unwrap();
+                    let count = store.entry(ch).or_insert(AtomicUsize::new(0));
+                    count.fetch_add(1, Ordering::Relaxed);
+                },
+                line,
+            );
+        }
+    }
+
+    pub fn frequency(input: &[&str], worker_count: usize) -> HashMap<char, usize> {
+        let store = RwLock::new(HashMap::<char, AtomicUsize>::new());
+
+        thread::scope(|scope| {
+            for chunk in super::split(input, worker_count) {
+                let store = &store;
+                scope.spawn(move || calc_frequency(chunk, store));
+            }
+        });
+
+        store
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|(k, v)| (k, v.into_inner()))
+            .collect::<HashMap<_, _>>()
+    }
+}
+
+pub mod work_stealing {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    pub fn frequency(input: &[&str], worker_count: usize) -> HashMap<char, usize> {
+        let worker_count = worker_count.clamp(1, input.len().max(1));
+        let cursor = AtomicUsize::new(0);
+
+        let stores = thread::scope(|scope| {
+            let handles = (0..worker_count)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut store = HashMap::<char, usize>::new();
+                        while let Some(line) = input.get(cursor.fetch_add(1, Ordering::Relaxed)) {
+                            super::process_line(
+                                |ch| {
+                                    *store.entry(ch).or_insert(0) += 1;
+                                },
+                                line,
+                            );
+                        }
+                        store
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        super::merge(stores)
+    }
+}
+
+pub mod lock_free {
+    use std::collections::HashMap;
+    use std::thread;
+
+    use super::counter::{AtomicCounter, LocalCounter};
+    use super::{process_line, split};
+
+    pub fn frequency(input: &[&str], worker_count: usize) -> HashMap<char, usize> {
+        let counter = AtomicCounter::new();
+
+        thread::scope(|scope| {
+            for chunk in split(input, worker_count) {
+                let counter = &counter;
+                scope.spawn(move || {
+                    for line in chunk {
+                        process_line(|ch| counter.add(ch), line);
+                    }
+                });
+            }
+        });
+
+        counter.into_map()
+    }
+
+    pub fn frequency_local(input: &[&str], worker_count: usize) -> HashMap<char, usize> {
+        let counter = AtomicCounter::new();
+
+        thread::scope(|scope| {
+            for chunk in split(input, worker_count) {
+                let counter = &counter;
+                scope.spawn(move || {
+                    let mut local = LocalCounter::new();
+                    for line in chunk {
+                        process_line(|ch| local.add(ch), line);
+                    }
+                    counter.absorb(local);
+                });
+            }
+        });
+
+        counter.into_map()
+    }
+}
+
+#[test]
+fn test_no_texts() {
+    for strategy in Strategy::VALUES {
+        assert_eq!(frequency_with(&[], 4, strategy), HashMap::new());
+    }
+}
+
+#[test]
+fn test_no_workers() {
+    for strategy in Strategy::VALUES {
+        assert_eq!(frequency_with(&["aA", "b"], 0, strategy), calc_frequency(&["aab"]));
+    }
+}
+
+#[test]
+fn test_more_workers_than_lines() {
+    for strategy in Strategy::VALUES {
+        assert_eq!(frequency_with(&["aA", "b"], 16, strategy), calc_frequency(&["aab"]));
+    }
+}
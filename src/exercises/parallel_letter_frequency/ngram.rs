@@ -0,0 +1,209 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::map_reduce;
+use super::options::CountOptions;
+
+/// Count runs of `n` consecutive counted chars, normalized by `options`.
+///
+/// N-grams never span a char that `options` doesn't count, so with the
+/// defaults they stay inside words. `n == 0` counts nothing.
+pub fn ngram_frequency(
+    input: &[&str],
+    worker_count: usize,
+    n: usize,
+    options: &CountOptions,
+) -> HashMap<String, usize> {
+    if n == 0 {
+        return HashMap::new();
+    }
+    map_reduce(input, worker_count, |lines| {
+        let mut store = HashMap::new();
+        let mut window = VecDeque::with_capacity(n);
+        let mut scratch = String::new();
+        for line in lines {
+            window.clear();
+            for (at, ch) in line.char_indices() {
+                if !options.keeps(ch) {
+                    window.clear();
+                    continue;
+                }
+                options.normalize(line, at, &mut |ch| {
+                    if window.len() == n {
+                        window.pop_front();
+                    }
+                    window.push_back(ch);
+                    if window.len() == n {
+                        scratch.clear();
+                        scratch.extend(window.iter());
+                        add(&mut store, &scratch);
+                    }
+                });
+            }
+        }
+        store
+    })
+}
+
+pub fn bigram_frequency(input: &[&str], worker_count: usize) -> HashMap<String, usize> {
+    ngram_frequency(input, worker_count, 2, &CountOptions::default())
+}
+
+pub fn trigram_frequency(input: &[&str], worker_count: usize) -> HashMap<String, usize> {
+    ngram_frequency(input, worker_count, 3, &CountOptions::default())
+}
+
+/// Count words as found by Unicode word segmentation (UAX #29), with every
+/// char normalized by `options`. Words left empty after normalization, such
+/// as plain numbers when digits aren't counted, are skipped.
+pub fn word_frequency(
+    input: &[&str],
+    worker_count: usize,
+    options: &CountOptions,
+) -> HashMap<String, usize> {
+    map_reduce(input, worker_count, |lines| {
+        let mut store = HashMap::new();
+        let mut scratch = String::new();
+        for line in lines {
+            for word in line.unicode_words() {
+                scratch.clear();
+                options.for_each_char(word, |ch| scratch.push(ch));
+                if !scratch.is_empty() {
+                    add(&mut store, &scratch);
+                }
+            }
+        }
+        store
+    })
+}
+
+/// The `k` most frequent n-grams, most frequent first.
+pub fn top_ngrams(
+    input: &[&str],
+    worker_count: usize,
+    n: usize,
+    k: usize,
+    options: &CountOptions,
+) -> Vec<(String, usize)> {
+    top_k(ngram_frequency(input, worker_count, n, options), k)
+}
+
+/// The `k` most frequent words, most frequent first.
+pub fn top_words(
+    input: &[&str],
+    worker_count: usize,
+    k: usize,
+    options: &CountOptions,
+) -> Vec<(String, usize)> {
+    top_k(word_frequency(input, worker_count, options), k)
+}
+
+/// The `k` entries with the highest counts, most frequent first and ties in
+/// key order.
+///
+/// Workers hand back exact counts and only the merged map is cut down:
+/// keeping a top `k` per worker would drop keys that are frequent overall but
+/// never make the cut in any single chunk. The selection itself keeps a heap
+/// of `k` entries, so it costs `O(n log k)` instead of sorting everything.
+pub fn top_k<K, I>(store: I, k: usize) -> Vec<(K, usize)>
+where
+    K: Ord,
+    I: IntoIterator<Item = (K, usize)>,
+{
+    if k == 0 {
+        return Vec::new();
+    }
+    // The heap top is the weakest entry kept so far: lowest count, and the
+    // greatest key among equal counts.
+    let mut heap = BinaryHeap::<(Reverse<usize>, K)>::with_capacity(k + 1);
+    for (key, count) in store {
+        heap.push((Reverse(count), key));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|(Reverse(count), key)| (key, count))
+        .collect()
+}
+
+fn add(store: &mut HashMap<String, usize>, key: &str) {
+    match store.get_mut(key) {
+        Some(count) => *count += 1,
+        None => {
+            store.insert(key.to_owned(), 1);
+        }
+    }
+}
+
+#[test]
+fn bigrams_stay_inside_words() {
+    let store = bigram_frequency(&["Abab, ba"], 2);
+    let mut store = store.into_iter().collect::<Vec<_>>();
+    store.sort();
+    assert_eq!(store, [("ab".to_owned(), 2), ("ba".to_owned(), 2)].to_vec());
+}
+
+#[test]
+fn trigrams_across_lines_and_workers() {
+    let lines = ["the then", "there", "", "ether"];
+    for worker_count in 0..5 {
+        let store = trigram_frequency(&lines, worker_count);
+        assert_eq!(store.get("the"), Some(&4));
+        assert_eq!(store.get("her"), Some(&2));
+        assert_eq!(store.get("hen"), Some(&1));
+        assert_eq!(store.get("et"), None);
+    }
+}
+
+#[test]
+fn ngrams_follow_case_folding() {
+    use super::options::CaseFolding;
+
+    let options = CountOptions::new().case_folding(CaseFolding::Full);
+    let store = ngram_frequency(&["Maße"], 1, 2, &options);
+    assert_eq!(store.get("ss"), Some(&1));
+    assert_eq!(store.get("as"), Some(&1));
+    assert_eq!(store.get("se"), Some(&1));
+}
+
+#[test]
+fn zero_length_ngrams() {
+    assert!(ngram_frequency(&["abc"], 2, 0, &CountOptions::new()).is_empty());
+}
+
+#[test]
+fn words_use_unicode_segmentation() {
+    let lines = [
+        "O say can you see, by the dawn's early light,",
+        "Say it, 1812!",
+    ];
+    let store = word_frequency(&lines, 2, &CountOptions::new());
+    assert_eq!(store.get("say"), Some(&2));
+    // The apostrophe is part of the word, but isn't a letter.
+    assert_eq!(store.get("dawns"), Some(&1));
+    assert_eq!(store.get("1812"), None);
+
+    let digits = CountOptions::new().digits(true);
+    assert_eq!(word_frequency(&lines, 2, &digits).get("1812"), Some(&1));
+}
+
+#[test]
+fn top_k_orders_by_count_then_key() {
+    let store = HashMap::from([('a', 3), ('b', 5), ('c', 3), ('d', 1)]);
+    assert_eq!(top_k(store.clone(), 3), vec![('b', 5), ('a', 3), ('c', 3)]);
+    assert_eq!(top_k(store.clone(), 10).len(), 4);
+    assert!(top_k(store, 0).is_empty());
+}
+
+#[test]
+fn top_words_of_anthem() {
+    let lines = ["O'er the land of the free and the home of the brave?"];
+    assert_eq!(
+        top_words(&lines, 2, 2, &CountOptions::new()),
+        vec![("the".to_owned(), 4), ("of".to_owned(), 2)]
+    );
+}
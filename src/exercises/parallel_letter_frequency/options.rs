@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::map_reduce;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseFolding {
+    /// Count `A` and `a` separately.
+    None,
+    /// Lowercase as `str::to_lowercase` does: `char::to_lowercase`, but a
+    /// `Σ` ending a word becomes `ς`.
+    #[default]
+    Lowercase,
+    /// Lowercase, and also fold the characters where full Unicode case
+    /// folding goes further: `ß` → `ss`, `ς` → `σ`, `ﬁ` → `fi` and so on.
+    Full,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Unit {
+    #[default]
+    Char,
+    /// Extended grapheme clusters, so `e` followed by a combining accent is
+    /// counted as one unit.
+    Grapheme,
+}
+
+/// What to count and how to normalize it before counting.
+///
+/// The default matches [`frequency`](super::frequency): letters only,
+/// lowercased, one `char` at a time.
+///
+/// Whether a `Σ` ends a word, and so lowercases to `ς`, is decided by the
+/// text around it in the line, spaces and punctuation included. The first
+/// counters lowercased a line's letters run together instead, which made
+/// `"ΟΔΟΣ ΟΔΟΣ"` one `σ` and one `ς`; it is two `ς` now, as
+/// `str::to_lowercase` has it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CountOptions {
+    case_folding: CaseFolding,
+    strip_diacritics: bool,
+    digits: bool,
+    punctuation: bool,
+    unit: Unit,
+}
+
+impl CountOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn case_folding(mut self, case_folding: CaseFolding) -> Self {
+        self.case_folding = case_folding;
+        self
+    }
+
+    /// Drop combining marks after canonical decomposition, so `é` counts as `e`.
+    pub fn strip_diacritics(mut self, strip: bool) -> Self {
+        self.strip_diacritics = strip;
+        self
+    }
+
+    pub fn digits(mut self, include: bool) -> Self {
+        self.digits = include;
+        self
+    }
+
+    /// Anything that isn't alphanumeric, whitespace, a control character or
+    /// a combining mark.
+    pub fn punctuation(mut self, include: bool) -> Self {
+        self.punctuation = include;
+        self
+    }
+
+    pub fn unit(mut self, unit: Unit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Call `emit` with every normalized `char` of `line` that should be
+    /// counted. Ignores [`Unit`]; nothing is allocated.
+    pub fn for_each_char<F: FnMut(char)>(&self, line: &str, mut emit: F) {
+        line.char_indices()
+            .filter(|&(_, ch)| self.keeps(ch))
+            .for_each(|(at, _)| self.normalize(line, at, &mut emit));
+    }
+
+    /// Call `emit` with every normalized unit of `line` that should be
+    /// counted. A grapheme is kept when its first `char` is; `scratch` is
+    /// reused for every unit so nothing is allocated once it has grown.
+    pub fn for_each_unit<F: FnMut(&str)>(&self, line: &str, scratch: &mut String, mut emit: F) {
+        match self.unit {
+            Unit::Char => self.for_each_char(line, |ch| emit(ch.encode_utf8(&mut [0; 4]))),
+            Unit::Grapheme => {
+                for (start, grapheme) in line.grapheme_indices(true) {
+                    if !grapheme.chars().next().is_some_and(|ch| self.keeps(ch)) {
+                        continue;
+                    }
+                    scratch.clear();
+                    for (at, _) in grapheme.char_indices() {
+                        self.normalize(line, start + at, &mut |ch| scratch.push(ch));
+                    }
+                    if !scratch.is_empty() {
+                        emit(scratch);
+                    }
+                }
+            }
+        }
+    }
+
+    pub(super) fn keeps(&self, ch: char) -> bool {
+        ch.is_alphabetic()
+            || (self.digits && ch.is_numeric())
+            || (self.punctuation
+                && !ch.is_alphanumeric()
+                && !ch.is_whitespace()
+                && !ch.is_control()
+                && !is_combining_mark(ch))
+    }
+
+    /// Normalize the `char` at byte `at` of `line`, which is needed to tell
+    /// a final sigma.
+    pub(super) fn normalize<F: FnMut(char)>(&self, line: &str, at: usize, emit: &mut F) {
+        let ch = line[at..].chars().next().expect("`at` starts a char");
+        let mut strip = |ch: char| {
+            if !self.strip_diacritics {
+                return emit(ch);
+            }
+            decompose_canonical(ch, |part| {
+                if !is_combining_mark(part) {
+                    emit(part);
+                }
+            });
+        };
+        match self.case_folding {
+            CaseFolding::None => strip(ch),
+            CaseFolding::Lowercase if ch == 'Σ' && is_final_sigma(line, at) => strip('ς'),
+            CaseFolding::Lowercase => ch.to_lowercase().for_each(strip),
+            CaseFolding::Full => match full_folding(ch) {
+                Some(folded) => folded.chars().for_each(strip),
+                None => ch.to_lowercase().for_each(strip),
+            },
+        }
+    }
+}
+
+/// Whether the `Σ` at byte `at` of `line` follows a letter and isn't followed
+/// by one, skipping combining marks either way.
+fn is_final_sigma(line: &str, at: usize) -> bool {
+    fn letter(mut chars: impl Iterator<Item = char>) -> bool {
+        chars
+            .find(|&ch| !is_combining_mark(ch))
+            .is_some_and(char::is_alphabetic)
+    }
+    letter(line[..at].chars().rev()) && !letter(line[at + 'Σ'.len_utf8()..].chars())
+}
+
+/// Full case foldings from Unicode's CaseFolding.txt that `to_lowercase`
+/// doesn't already produce.
+fn full_folding(ch: char) -> Option<&'static str> {
+    let folded = match ch {
+        'ß' | 'ẞ' => "ss",
+        'ſ' => "s",
+        'ŉ' => "\u{2BC}n",
+        'ǰ' => "j\u{30C}",
+        'ẖ' => "h\u{331}",
+        'ẗ' => "t\u{308}",
+        'ẘ' => "w\u{30A}",
+        'ẙ' => "y\u{30A}",
+        'ẚ' => "a\u{2BE}",
+        'ẛ' => "ṡ",
+        'ς' => "σ",
+        'ϐ' => "β",
+        'ϑ' => "θ",
+        'ϕ' => "φ",
+        'ϖ' => "π",
+        'ϰ' => "κ",
+        'ϱ' => "ρ",
+        'ϵ' => "ε",
+        'ᾳ' | 'ᾼ' => "αι",
+        'ῃ' | 'ῌ' => "ηι",
+        'ῳ' | 'ῼ' => "ωι",
+        'և' => "եւ",
+        'ﬀ' => "ff",
+        'ﬁ' => "fi",
+        'ﬂ' => "fl",
+        'ﬃ' => "ffi",
+        'ﬄ' => "ffl",
+        'ﬅ' | 'ﬆ' => "st",
+        _ => return None,
+    };
+    Some(folded)
+}
+
+/// Count units of `input` as configured by `options`, keyed by the
+/// normalized unit.
+pub fn frequency_with_options(
+    input: &[&str],
+    worker_count: usize,
+    options: &CountOptions,
+) -> HashMap<String, usize> {
+    map_reduce(input, worker_count, |lines| {
+        let mut store = HashMap::<String, usize>::new();
+        let mut scratch = String::new();
+        for line in lines {
+            options.for_each_unit(line, &mut scratch, |unit| match store.get_mut(unit) {
+                Some(count) => *count += 1,
+                None => {
+                    store.insert(unit.to_owned(), 1);
+                }
+            });
+        }
+        store
+    })
+}
+
+fn count(line: &str, options: &CountOptions) -> Vec<(String, usize)> {
+    let mut store = frequency_with_options(&[line], 1, options)
+        .into_iter()
+        .collect::<Vec<_>>();
+    store.sort();
+    store
+}
+
+fn units(counts: &[(&str, usize)]) -> Vec<(String, usize)> {
+    counts
+        .iter()
+        .map(|&(unit, count)| (unit.to_owned(), count))
+        .collect()
+}
+
+#[test]
+fn default_matches_frequency() {
+    let line = "Freude schöner Götterfunken, 1 2 3!";
+    let expected = super::frequency(&[line], 1)
+        .into_iter()
+        .map(|(ch, count)| (ch.to_string(), count))
+        .collect::<HashMap<_, _>>();
+    assert_eq!(
+        frequency_with_options(&[line], 2, &CountOptions::new()),
+        expected
+    );
+}
+
+#[test]
+fn no_case_folding() {
+    let options = CountOptions::new().case_folding(CaseFolding::None);
+    assert_eq!(count("aAa", &options), units(&[("A", 1), ("a", 2)]));
+}
+
+#[test]
+fn full_case_folding() {
+    let lowercase = CountOptions::new();
+    assert_eq!(count("Straße", &lowercase)[0], ("a".to_owned(), 1));
+    assert!(count("Straße", &lowercase).contains(&("ß".to_owned(), 1)));
+
+    let full = CountOptions::new().case_folding(CaseFolding::Full);
+    assert_eq!(
+        count("Straße ﬁ", &full),
+        units(&[
+            ("a", 1),
+            ("e", 1),
+            ("f", 1),
+            ("i", 1),
+            ("r", 1),
+            ("s", 3),
+            ("t", 1)
+        ])
+    );
+}
+
+#[test]
+fn final_sigma() {
+    let expected = HashMap::from([('ο', 2), ('δ', 1), ('ς', 1)]);
+    assert_eq!(super::frequency(&["ΟΔΟΣ"], 1), expected);
+    let expected = HashMap::from([('ο', 4), ('δ', 2), ('ς', 2)]);
+    assert_eq!(super::frequency(&["ΟΔΟΣ ΟΔΟΣ"], 1), expected);
+
+    let line = "ΣΟΦΟΣ, Σ ΟΔΟΣ\u{301}";
+    let lowercase = count(line, &CountOptions::new());
+    assert!(lowercase.contains(&("σ".to_owned(), 2)));
+    assert!(lowercase.contains(&("ς".to_owned(), 2)));
+    let graphemes = count(line, &CountOptions::new().unit(Unit::Grapheme));
+    assert!(graphemes.contains(&("ς\u{301}".to_owned(), 1)));
+    let full = count(line, &CountOptions::new().case_folding(CaseFolding::Full));
+    assert!(full.contains(&("σ".to_owned(), 4)));
+}
+
+#[test]
+fn diacritics_stripped() {
+    let options = CountOptions::new().strip_diacritics(true);
+    assert_eq!(
+        count("Éé geëerd", &options),
+        units(&[("d", 1), ("e", 5), ("g", 1), ("r", 1)])
+    );
+}
+
+#[test]
+fn digits_and_punctuation() {
+    let line = "Testing, 1, 2!";
+    let digits = CountOptions::new().digits(true);
+    assert!(count(line, &digits).contains(&("1".to_owned(), 1)));
+    assert!(!count(line, &digits).contains(&(",".to_owned(), 2)));
+
+    let punctuation = CountOptions::new().punctuation(true);
+    assert!(count(line, &punctuation).contains(&(",".to_owned(), 2)));
+    assert!(count(line, &punctuation).contains(&("!".to_owned(), 1)));
+    assert!(!count(line, &punctuation).contains(&("1".to_owned(), 1)));
+}
+
+#[test]
+fn grapheme_units() {
+    // "e" + combining acute, twice, and a precomposed "é".
+    let line = "e\u{301}E\u{301}é";
+    let graphemes = CountOptions::new().unit(Unit::Grapheme);
+    assert_eq!(count(line, &graphemes), units(&[("e\u{301}", 2), ("é", 1)]));
+
+    let stripped = graphemes.strip_diacritics(true);
+    assert_eq!(count(line, &stripped), units(&[("e", 3)]));
+}
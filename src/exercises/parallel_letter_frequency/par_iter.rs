@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+use super::counter::LocalCounter;
+use super::frequencies::Frequencies;
+use super::process_line;
+
+/// Count letters with rayon: every split of the input folds into its own
+/// counter and the counters are reduced pairwise. Runs on the current rayon
+/// pool, the global one unless called inside [`ThreadPool::install`].
+pub fn frequency(input: &[&str]) -> HashMap<char, usize> {
+    input
+        .par_iter()
+        .fold(LocalCounter::new, |mut counter, line| {
+            process_line(|ch| counter.add(ch), line);
+            counter
+        })
+        .map(|counter| Frequencies::from(counter.into_map()))
+        .reduce(Frequencies::new, |a, b| a + b)
+        .into_inner()
+}
+
+/// Count letters on a dedicated rayon pool.
+pub fn frequency_in(pool: &ThreadPool, input: &[&str]) -> HashMap<char, usize> {
+    pool.install(|| frequency(input))
+}
+
+#[test]
+fn rayon_matches_scope() {
+    let lines = ["Freude schöner Götterfunken", "", "heb ik altijd geëerd."].repeat(300);
+    assert_eq!(frequency(&lines), super::scope::frequency(&lines, 3));
+    assert_eq!(frequency(&[]), HashMap::new());
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    assert_eq!(
+        frequency_in(&pool, &lines),
+        super::scope::frequency(&lines, 3)
+    );
+}
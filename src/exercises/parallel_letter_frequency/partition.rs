@@ -0,0 +1,109 @@
+use std::ops::Range;
+
+/// Split `len` lines into at most `parts` contiguous ranges whose sizes differ
+/// by at most one.
+///
+/// Empty input gives no ranges, zero parts is treated as one, and there are
+/// never more ranges than lines, so no range is empty.
+pub fn by_line_count(len: usize, parts: usize) -> Vec<Range<usize>> {
+    let parts = parts.clamp(1, len.max(1));
+    let (size, extra) = (len / parts, len % parts);
+
+    let mut start = 0;
+    (0..parts)
+        .map(|part| {
+            let end = start + size + usize::from(part < extra);
+            let range = start..end;
+            start = end;
+            range
+        })
+        .filter(|range| !range.is_empty())
+        .collect()
+}
+
+/// Split lines into at most `parts` contiguous ranges holding roughly the same
+/// number of bytes.
+///
+/// Ranges are cut where the running byte total crosses each part's share of
+/// the whole, so a few long lines don't end up on the same worker. Falls back
+/// to [`by_line_count`] when there are no bytes to balance.
+pub fn by_byte_size(input: &[&str], parts: usize) -> Vec<Range<usize>> {
+    let total = input.iter().map(|line| line.len()).sum::<usize>();
+    let parts = parts.clamp(1, input.len().max(1));
+    if total == 0 || parts == 1 {
+        return by_line_count(input.len(), parts);
+    }
+
+    let mut ranges = Vec::with_capacity(parts);
+    let (mut start, mut acc) = (0, 0);
+    for (index, line) in input.iter().enumerate() {
+        acc += line.len();
+        let lines_left = input.len() - index - 1;
+        let parts_left = parts - ranges.len() - 1;
+        // Cut when this part has its share, and keep at least one line for
+        // every part still to come.
+        if parts_left > 0 && (acc * parts >= total * (ranges.len() + 1) || lines_left == parts_left)
+        {
+            ranges.push(start..index + 1);
+            start = index + 1;
+        }
+    }
+    if start < input.len() {
+        ranges.push(start..input.len());
+    }
+    ranges
+}
+
+/// Chunks of `input` as given by [`by_byte_size`].
+pub fn chunks<'a, 'b>(input: &'a [&'b str], parts: usize) -> impl Iterator<Item = &'a [&'b str]> {
+    by_byte_size(input, parts)
+        .into_iter()
+        .map(move |range| &input[range])
+}
+
+#[test]
+fn line_count_of_empty_input() {
+    assert!(by_line_count(0, 4).is_empty());
+    assert!(by_line_count(0, 0).is_empty());
+}
+
+#[test]
+fn line_count_with_zero_parts() {
+    assert_eq!(by_line_count(5, 0), vec![0..5]);
+}
+
+#[test]
+fn line_count_with_more_parts_than_lines() {
+    assert_eq!(by_line_count(3, 8), vec![0..1, 1..2, 2..3]);
+}
+
+#[test]
+fn line_count_is_balanced() {
+    // The old `len / n + len % n` math gave 3 + 3 + 3 + 1 here.
+    assert_eq!(by_line_count(10, 4), vec![0..3, 3..6, 6..8, 8..10]);
+}
+
+#[test]
+fn byte_size_of_empty_input() {
+    assert!(by_byte_size(&[], 4).is_empty());
+    assert_eq!(by_byte_size(&["", "", ""], 2), vec![0..2, 2..3]);
+}
+
+#[test]
+fn byte_size_follows_bytes_not_lines() {
+    let input = ["a", "b", "cccccccccccccccccccccccc", "d", "e", "f"];
+    assert_eq!(by_byte_size(&input, 2), vec![0..3, 3..6]);
+    assert_eq!(by_byte_size(&input, 3), vec![0..3, 3..4, 4..6]);
+}
+
+#[test]
+fn byte_size_covers_every_line_once() {
+    let input = ["ab", "", "cde", "f", "", "ghij", "k"];
+    for parts in 0..10 {
+        let ranges = by_byte_size(&input, parts);
+        assert!(ranges.len() <= parts.max(1));
+        assert!(ranges.iter().all(|range| !range.is_empty()));
+        let flat = ranges.into_iter().flatten().collect::<Vec<_>>();
+        assert_eq!(flat, (0..input.len()).collect::<Vec<_>>());
+    }
+}
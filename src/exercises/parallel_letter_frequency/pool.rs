@@ -0,0 +1,479 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+use std::task::Waker;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::counter::LocalCounter;
+use super::{merge, partition, process_line};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolError {
+    /// The job was cancelled before every part of it was counted.
+    Cancelled,
+    /// The job didn't finish in time and has been cancelled.
+    TimedOut,
+    /// A task panicked before reporting back. Its worker carries on.
+    WorkerLost,
+    /// Every worker is gone, so nothing could take the job.
+    Closed,
+}
+
+impl Display for PoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PoolError::Cancelled => write!(f, "job was cancelled"),
+            PoolError::TimedOut => write!(f, "job timed out"),
+            PoolError::WorkerLost => write!(f, "worker panicked without a result"),
+            PoolError::Closed => write!(f, "pool has no workers left"),
+        }
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+struct Task {
+    lines: Arc<[String]>,
+    range: Range<usize>,
+    cancelled: Arc<AtomicBool>,
+    result: mpsc::Sender<HashMap<char, usize>>,
+    /// After `result`, so the sender is gone by the time the job is woken.
+    waker: WakeOnDrop,
+}
+
+/// Wakes the job's future when the task is dropped, however it ends: with a
+/// result, cancelled or by panicking.
+struct WakeOnDrop(Arc<Mutex<Option<Waker>>>);
+
+impl Drop for WakeOnDrop {
+    fn drop(&mut self) {
+        // Also runs while a task unwinds, so it must not panic itself.
+        let waker = self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl Task {
+    /// Count the task's lines, giving up between lines once the job is
+    /// cancelled. A cancelled task never reports back, but still wakes the
+    /// job, as it's dropped, so a pending future can notice.
+    fn run(self) {
+        let mut counter = LocalCounter::new();
+        let mut cancelled = false;
+        for line in &self.lines[self.range.clone()] {
+            cancelled = self.cancelled.load(Ordering::Relaxed);
+            if cancelled {
+                break;
+            }
+            process_line(|ch| counter.add(ch), line);
+        }
+        if !cancelled {
+            // The job may have been dropped while we were counting.
+            let _ = self.result.send(counter.into_map());
+        }
+    }
+}
+
+/// A fixed set of worker threads that count letters for any number of jobs.
+///
+/// Jobs are cut into one task per worker and queued on a shared channel, so
+/// consecutive calls reuse the same threads instead of spawning new ones.
+/// A task that panics fails its job, not its worker, so the pool keeps its
+/// size. Dropping the pool lets the workers finish what is already queued and
+/// then joins them.
+pub struct FrequencyPool {
+    sender: Option<mpsc::Sender<Task>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl FrequencyPool {
+    pub fn new(worker_count: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..worker_count.max(1))
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || loop {
+                    // The guard is dropped before the task runs.
+                    let task = receiver.lock().unwrap().recv();
+                    match task {
+                        // The job sees the task's sender drop and reports the
+                        // panic itself.
+                        Ok(task) => {
+                            let _ = panic::catch_unwind(AssertUnwindSafe(|| task.run()));
+                        }
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Queue `input` for counting and return a handle to its result.
+    pub fn submit<I, S>(&self, input: I) -> Result<FrequencyJob, PoolError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let lines = input.into_iter().map(Into::into).collect::<Arc<[String]>>();
+        let ranges = {
+            let refs = lines.iter().map(String::as_str).collect::<Vec<_>>();
+            partition::by_byte_size(&refs, self.worker_count())
+        };
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let waker = Arc::new(Mutex::new(None));
+        let (result, results) = mpsc::channel();
+        let sender = self.sender.as_ref().unwrap();
+        for range in &ranges {
+            let task = Task {
+                lines: Arc::clone(&lines),
+                range: range.clone(),
+                cancelled: Arc::clone(&cancelled),
+                result: result.clone(),
+                waker: WakeOnDrop(Arc::clone(&waker)),
+            };
+            // Workers only stop once the sender is dropped, with the pool, so
+            // this fails only if they all died some other way.
+            sender.send(task).map_err(|_| PoolError::Closed)?;
+        }
+
+        Ok(FrequencyJob {
+            cancelled,
+            results,
+            pending: ranges.len(),
+            stores: Vec::with_capacity(ranges.len()),
+            waker,
+        })
+    }
+
+    /// Count `input` on the pool and wait for the result.
+    pub fn frequency(&self, input: &[&str]) -> HashMap<char, usize> {
+        self.submit(input.iter().copied())
+            .and_then(FrequencyJob::wait)
+            .expect("counting an uncancelled job without a deadline doesn't panic")
+    }
+}
+
+impl Drop for FrequencyPool {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Handle to a job queued on a [`FrequencyPool`].
+///
+/// Either block on it with [`wait`](Self::wait), or, with the `async`
+/// feature, `.await` it: the counting stays on the pool's threads and the
+/// future is woken as parts of the job finish.
+pub struct FrequencyJob {
+    cancelled: Arc<AtomicBool>,
+    results: mpsc::Receiver<HashMap<char, usize>>,
+    pending: usize,
+    stores: Vec<HashMap<char, usize>>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl FrequencyJob {
+    /// Stop counting this job. Tasks still queued are skipped and running
+    /// ones stop at the next line.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// A handle that cancels this job from another thread.
+    pub fn canceller(&self) -> Canceller {
+        Canceller(Arc::clone(&self.cancelled))
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn wait(self) -> Result<HashMap<char, usize>, PoolError> {
+        self.collect(None)
+    }
+
+    /// Wait at most `timeout`; a job that runs late is cancelled.
+    pub fn wait_timeout(self, timeout: Duration) -> Result<HashMap<char, usize>, PoolError> {
+        self.collect(Some(Instant::now() + timeout))
+    }
+
+    fn collect(mut self, deadline: Option<Instant>) -> Result<HashMap<char, usize>, PoolError> {
+        while self.stores.len() < self.pending {
+            let store = match deadline {
+                None => self.results.recv().map_err(|_| self.lost()),
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    self.results.recv_timeout(timeout).map_err(|err| match err {
+                        mpsc::RecvTimeoutError::Timeout => {
+                            self.cancel();
+                            PoolError::TimedOut
+                        }
+                        mpsc::RecvTimeoutError::Disconnected => self.lost(),
+                    })
+                }
+            }?;
+            self.stores.push(store);
+        }
+        if self.is_cancelled() {
+            return Err(PoolError::Cancelled);
+        }
+        Ok(merge(self.stores))
+    }
+
+    fn lost(&self) -> PoolError {
+        if self.is_cancelled() {
+            PoolError::Cancelled
+        } else {
+            PoolError::WorkerLost
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl std::future::Future for FrequencyJob {
+    type Output = Result<HashMap<char, usize>, PoolError>;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        use std::task::Poll;
+
+        // Registered before looking at the results, so a task finishing in
+        // between still wakes us.
+        *self.waker.lock().unwrap() = Some(cx.waker().clone());
+        loop {
+            if self.is_cancelled() {
+                return Poll::Ready(Err(PoolError::Cancelled));
+            }
+            if self.stores.len() == self.pending {
+                return Poll::Ready(Ok(merge(std::mem::take(&mut self.stores))));
+            }
+            match self.results.try_recv() {
+                Ok(store) => self.stores.push(store),
+                Err(mpsc::TryRecvError::Empty) => return Poll::Pending,
+                Err(mpsc::TryRecvError::Disconnected) => return Poll::Ready(Err(self.lost())),
+            }
+        }
+    }
+}
+
+/// Count `input` on a pool shared by the whole process, sized to the
+/// machine, and return the job to `.await`. Needs no particular runtime.
+/// Never fails to submit: the pool is never dropped and its workers outlive
+/// panicking tasks.
+#[cfg(feature = "async")]
+pub fn frequency_async<I, S>(input: I) -> FrequencyJob
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    static POOL: std::sync::OnceLock<FrequencyPool> = std::sync::OnceLock::new();
+    POOL.get_or_init(|| {
+        let worker_count = thread::available_parallelism().map_or(1, |n| n.get());
+        FrequencyPool::new(worker_count)
+    })
+    .submit(input)
+    .expect("the shared pool always has workers")
+}
+
+/// Cancels the [`FrequencyJob`] it was taken from.
+#[derive(Debug, Clone)]
+pub struct Canceller(Arc<AtomicBool>);
+
+impl Canceller {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn pool_matches_frequency() {
+    let lines = [
+        "Freude schöner Götterfunken",
+        "Tochter aus Elysium,",
+        "",
+        "ü",
+    ];
+    let pool = FrequencyPool::new(3);
+    for _ in 0..100 {
+        assert_eq!(pool.frequency(&lines), super::frequency(&lines, 1));
+    }
+    assert_eq!(pool.worker_count(), 3);
+}
+
+#[test]
+fn pool_with_no_input_or_workers() {
+    let pool = FrequencyPool::new(0);
+    assert_eq!(pool.worker_count(), 1);
+    assert_eq!(pool.frequency(&[]), HashMap::new());
+    assert_eq!(pool.frequency(&["aA"]), HashMap::from([('a', 2)]));
+}
+
+#[test]
+fn cancelled_job() {
+    let pool = FrequencyPool::new(2);
+    let job = pool.submit(vec!["abc"; 10_000]).unwrap();
+    job.canceller().cancel();
+    assert_eq!(job.wait(), Err(PoolError::Cancelled));
+
+    // The pool is still usable afterwards.
+    assert_eq!(pool.frequency(&["b"]), HashMap::from([('b', 1)]));
+}
+
+#[test]
+fn timed_out_job() {
+    let pool = FrequencyPool::new(1);
+    // Keeps the only worker busy, so the second job can't even start.
+    let busy = pool
+        .submit(vec!["abcdefghijklmnopqrstuvwxyz"; 200_000])
+        .unwrap();
+    let late = pool.submit(["abc"]).unwrap();
+    assert_eq!(late.wait_timeout(Duration::ZERO), Err(PoolError::TimedOut));
+    busy.cancel();
+    assert_eq!(busy.wait(), Err(PoolError::Cancelled));
+}
+
+/// Queue a task on `pool` that panics as soon as it runs, for a job of its
+/// own.
+fn submit_panicking_task(pool: &FrequencyPool) -> FrequencyJob {
+    let (result, results) = mpsc::channel();
+    let job = FrequencyJob {
+        cancelled: Arc::new(AtomicBool::new(false)),
+        results,
+        pending: 1,
+        stores: Vec::new(),
+        waker: Arc::new(Mutex::new(None)),
+    };
+    let task = Task {
+        lines: Arc::from([]),
+        // Out of bounds.
+        range: 0..1,
+        cancelled: Arc::clone(&job.cancelled),
+        result,
+        waker: WakeOnDrop(Arc::clone(&job.waker)),
+    };
+    pool.sender.as_ref().unwrap().send(task).unwrap();
+    job
+}
+
+#[test]
+fn panicking_task_fails_only_its_job() {
+    let pool = FrequencyPool::new(2);
+    for _ in 0..4 {
+        let job = submit_panicking_task(&pool);
+        assert_eq!(job.wait(), Err(PoolError::WorkerLost));
+    }
+    assert_eq!(pool.worker_count(), 2);
+    assert!(pool.workers.iter().all(|worker| !worker.is_finished()));
+    assert_eq!(
+        pool.frequency(&["ab", "b"]),
+        HashMap::from([('a', 1), ('b', 2)])
+    );
+}
+
+#[test]
+fn submitting_without_workers() {
+    let mut pool = FrequencyPool::new(1);
+    // Stand-in for every worker having died.
+    let (sender, receiver) = mpsc::channel();
+    drop(receiver);
+    pool.sender = Some(sender);
+    assert_eq!(pool.submit(["abc"]).err(), Some(PoolError::Closed));
+}
+
+#[test]
+fn dropping_pool_finishes_queued_jobs() {
+    let pool = FrequencyPool::new(2);
+    let job = pool.submit(vec!["ab"; 1_000]).unwrap();
+    drop(pool);
+    assert_eq!(job.wait(), Ok(HashMap::from([('a', 1_000), ('b', 1_000)])));
+}
+
+/// Just enough of an executor to drive one future on the current thread.
+#[cfg(feature = "async")]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, Wake};
+
+    struct Unpark(thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "async")]
+fn awaited_job_matches_scope() {
+    let lines = ["Freude schöner Götterfunken", "", "heb ik altijd geëerd."].repeat(300);
+    let expected = super::scope::frequency(&lines, 3);
+
+    let pool = FrequencyPool::new(3);
+    assert_eq!(
+        block_on(pool.submit(lines.clone()).unwrap()),
+        Ok(expected.clone())
+    );
+    assert_eq!(block_on(frequency_async(lines)), Ok(expected));
+    assert_eq!(
+        block_on(frequency_async(Vec::<String>::new())),
+        Ok(HashMap::new())
+    );
+}
+
+#[test]
+#[cfg(feature = "async")]
+fn awaited_job_cancelled() {
+    let pool = FrequencyPool::new(1);
+    let job = pool.submit(vec!["abc"; 100_000]).unwrap();
+    job.cancel();
+    assert_eq!(block_on(job), Err(PoolError::Cancelled));
+}
+
+#[test]
+#[cfg(feature = "async")]
+fn awaited_job_with_panicking_task() {
+    let pool = FrequencyPool::new(2);
+    for _ in 0..20 {
+        assert_eq!(
+            block_on(submit_panicking_task(&pool)),
+            Err(PoolError::WorkerLost)
+        );
+    }
+    assert_eq!(
+        block_on(pool.submit(["ab"]).unwrap()),
+        Ok(HashMap::from([('a', 1), ('b', 1)]))
+    );
+}
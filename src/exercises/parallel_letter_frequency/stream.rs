@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+use super::counter::LocalCounter;
+use super::{merge, process_line};
+
+/// Bytes read from the source before a block is handed to a worker.
+pub const BLOCK_SIZE: usize = 1 << 20;
+
+/// Count letters in everything `reader` yields.
+///
+/// The calling thread reads blocks and hands them to `worker_count` workers
+/// over a bounded channel, so no more than a couple of blocks per worker are
+/// held in memory at once. Blocks end on a line break when there is one, and
+/// never inside a UTF-8 sequence.
+pub fn frequency_from_reader<R: Read>(
+    reader: R,
+    worker_count: usize,
+) -> io::Result<HashMap<char, usize>> {
+    frequency_from_blocks(Blocks::new(reader, BLOCK_SIZE), worker_count)
+}
+
+/// Count letters in the file at `path`.
+///
+/// The file is cut into `worker_count` byte ranges aligned to line starts and
+/// every worker streams its own range through a separate handle.
+pub fn frequency_from_path<P: AsRef<Path>>(
+    path: P,
+    worker_count: usize,
+) -> io::Result<HashMap<char, usize>> {
+    frequency_from_file(path.as_ref(), worker_count, BLOCK_SIZE)
+}
+
+fn frequency_from_blocks<R: Read>(
+    blocks: Blocks<R>,
+    worker_count: usize,
+) -> io::Result<HashMap<char, usize>> {
+    let worker_count = worker_count.max(1);
+    let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(worker_count);
+    let receiver = Mutex::new(receiver);
+    let failed = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        let workers = (0..worker_count)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut counter = LocalCounter::new();
+                    let mut result = Ok(());
+                    loop {
+                        // The guard is dropped before counting starts.
+                        let block = receiver.lock().unwrap().recv();
+                        let Ok(block) = block else {
+                            return result.map(|()| counter.into_map());
+                        };
+                        // After an error keep draining, so the reader never
+                        // blocks on a full channel.
+                        if result.is_ok() {
+                            result = count_block(&block, &mut counter);
+                            failed.fetch_or(result.is_err(), Ordering::Relaxed);
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut read_result = Ok(());
+        for block in blocks {
+            if failed.load(Ordering::Relaxed) {
+                break;
+            }
+            match block {
+                Ok(block) => sender.send(block).unwrap(),
+                Err(err) => {
+                    read_result = Err(err);
+                    break;
+                }
+            }
+        }
+        drop(sender);
+
+        let stores = workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect::<io::Result<Vec<_>>>();
+        read_result?;
+        Ok(merge(stores?))
+    })
+}
+
+fn frequency_from_file(
+    path: &Path,
+    worker_count: usize,
+    block_size: usize,
+) -> io::Result<HashMap<char, usize>> {
+    let len = File::open(path)?.metadata()?.len();
+    let worker_count = worker_count.max(1) as u64;
+
+    let mut bounds = vec![0];
+    for part in 1..worker_count {
+        let start = line_start_after(path, len * part / worker_count)?;
+        if start > *bounds.last().unwrap() && start < len {
+            bounds.push(start);
+        }
+    }
+    bounds.push(len);
+
+    thread::scope(|scope| {
+        let workers = bounds
+            .windows(2)
+            .map(|range| {
+                let (start, end) = (range[0], range[1]);
+                scope.spawn(move || {
+                    let mut file = File::open(path)?;
+                    file.seek(SeekFrom::Start(start))?;
+                    let mut counter = LocalCounter::new();
+                    for block in Blocks::new(file.take(end - start), block_size) {
+                        count_block(&block?, &mut counter)?;
+                    }
+                    Ok(counter.into_map())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let stores = workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(merge(stores))
+    })
+}
+
+/// Offset of the first line that starts at or after `offset`, or the file
+/// length if there is none.
+fn line_start_after(path: &Path, offset: u64) -> io::Result<u64> {
+    if offset == 0 {
+        return Ok(0);
+    }
+    let mut file = File::open(path)?;
+    // Starting one byte early keeps a line that begins exactly at `offset`.
+    file.seek(SeekFrom::Start(offset - 1))?;
+    let mut reader = BufReader::new(file);
+    let mut position = offset - 1;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(position);
+        }
+        if let Some(index) = buf.iter().position(|&byte| byte == b'\n') {
+            return Ok(position + index as u64 + 1);
+        }
+        let len = buf.len();
+        position += len as u64;
+        reader.consume(len);
+    }
+}
+
+fn count_block(block: &[u8], counter: &mut LocalCounter) -> io::Result<()> {
+    let text = std::str::from_utf8(block)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    process_line(|ch| counter.add(ch), text);
+    Ok(())
+}
+
+/// Reads a source in blocks of about `block_size` bytes, cut after the last
+/// line break in the block, or after the last complete UTF-8 sequence when
+/// the block has no line break.
+struct Blocks<R> {
+    reader: R,
+    block_size: usize,
+    carry: Vec<u8>,
+    done: bool,
+}
+
+impl<R: Read> Blocks<R> {
+    fn new(reader: R, block_size: usize) -> Self {
+        Self {
+            reader,
+            block_size: block_size.max(4),
+            carry: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Read until `buf` grows by `block_size` bytes. Returns `false` on EOF.
+    fn fill(&mut self, buf: &mut Vec<u8>) -> io::Result<bool> {
+        let mut filled = buf.len();
+        let limit = filled + self.block_size;
+        buf.resize(limit, 0);
+        while filled < limit {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) => {
+                    buf.truncate(filled);
+                    return Ok(false);
+                }
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    buf.truncate(filled);
+                    return Err(err);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read> Iterator for Blocks<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut buf = std::mem::take(&mut self.carry);
+        match self.fill(&mut buf) {
+            Ok(true) => {}
+            Ok(false) => {
+                self.done = true;
+                return (!buf.is_empty()).then_some(Ok(buf));
+            }
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err));
+            }
+        }
+        let cut = match buf.iter().rposition(|&byte| byte == b'\n') {
+            Some(index) => index + 1,
+            None => utf8_boundary(&buf),
+        };
+        self.carry = buf.split_off(cut);
+        Some(Ok(buf))
+    }
+}
+
+/// Length of the longest prefix of `buf` that doesn't end inside a UTF-8
+/// sequence. Invalid input is left whole for `from_utf8` to report.
+fn utf8_boundary(buf: &[u8]) -> usize {
+    let lead = buf
+        .iter()
+        .rev()
+        .take(4)
+        .position(|&byte| byte & 0b1100_0000 != 0b1000_0000);
+    let Some(back) = lead else {
+        return buf.len();
+    };
+    let start = buf.len() - back - 1;
+    let width = match buf[start] {
+        byte if byte < 0x80 => 1,
+        byte if byte >= 0xF0 => 4,
+        byte if byte >= 0xE0 => 3,
+        _ => 2,
+    };
+    if start + width > buf.len() {
+        start
+    } else {
+        buf.len()
+    }
+}
+
+/// Hands out at most one byte per `read`, to push every block boundary
+/// through the carry logic.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some((&first, rest)) = self.0.split_first() else {
+            return Ok(0);
+        };
+        match buf.first_mut() {
+            Some(byte) => *byte = first,
+            None => return Ok(0),
+        }
+        self.0 = rest;
+        Ok(1)
+    }
+}
+
+#[test]
+fn reader_matches_in_memory_count() {
+    let text = "Freude schöner Götterfunken\nTochter aus Elysium,\nheb ik altijd geëerd.";
+    let expected = super::calc_frequency(&[text]);
+    assert_eq!(frequency_from_reader(text.as_bytes(), 3).unwrap(), expected);
+}
+
+#[test]
+fn small_blocks_never_split_a_char() {
+    let text = "ёжик в тумане 🦔 ärger über öl\nnächste zeile";
+    let expected = super::calc_frequency(&[text]);
+    for block_size in 4..12 {
+        let blocks = Blocks::new(Trickle(text.as_bytes()), block_size);
+        assert_eq!(frequency_from_blocks(blocks, 2).unwrap(), expected);
+    }
+}
+
+#[test]
+fn invalid_utf8_is_an_error() {
+    let err = frequency_from_reader(&b"abc\xFFdef"[..], 2).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn empty_reader() {
+    assert_eq!(
+        frequency_from_reader(io::empty(), 4).unwrap(),
+        HashMap::new()
+    );
+}
+
+#[test]
+fn file_ranges_match_in_memory_count() {
+    let lines = [
+        "Wilhelmus van Nassouwe",
+        "ben ik, van Duitsen bloed,",
+        "",
+        "heb ik altijd geëerd.",
+    ];
+    let text = lines.join("\n").repeat(50);
+    let path = std::env::temp_dir().join(format!("letter_frequency_{}.txt", std::process::id()));
+    std::fs::write(&path, &text).unwrap();
+
+    let expected = super::calc_frequency(&[&text]);
+    for worker_count in 0..6 {
+        assert_eq!(
+            frequency_from_file(&path, worker_count, 16).unwrap(),
+            expected
+        );
+    }
+    assert_eq!(frequency_from_path(&path, 3).unwrap(), expected);
+
+    std::fs::remove_file(&path).unwrap();
+}
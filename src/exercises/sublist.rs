@@ -1,4 +1,4 @@
-mod sublist {
+pub mod sublist {
     #[derive(Debug, PartialEq, Eq)]
     pub enum Comparison {
         Equal,
@@ -1,4 +1,4 @@
-mod tournament {
+pub mod tournament {
     use std::{cmp::Ordering, collections::HashMap, ops::Neg, str::FromStr};
 
     #[derive(Clone, Copy)]
@@ -93,7 +93,6 @@ mod tournament {
         let mut output = "Team                           | MP |  W |  D |  L |  P".to_owned();
         let mut results: HashMap<&str, Team> = HashMap::new();
         for line in input.lines() {
-            println!("line: {}", line);
             let Some((team1_name, team2_name, match_result)) = parse_line(line) else {
                 continue;
             };
@@ -116,7 +115,6 @@ mod tournament {
         table.sort();
 
         for team in table {
-            println!("{:?}", team);
             output += &format!(
                 "\n{team_name:<31}|  {MP} |  {W} |  {D} |  {L} |  {P}",
                 team_name = team.name,
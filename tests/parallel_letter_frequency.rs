@@ -119,6 +119,54 @@ fn test_non_integer_multiple_of_threads() {
     assert_eq!(frequency::frequency(&v[..], 4), hm);
 }
 
+#[test]
+fn test_all_strategies_agree() {
+    let mut v = Vec::new();
+    for anthem in [ODE_AN_DIE_FREUDE, WILHELMUS, STAR_SPANGLED_BANNER].iter() {
+        for line in anthem.iter() {
+            v.push(*line);
+        }
+    }
+    let expected = frequency::frequency_with(&v[..], 1, frequency::Strategy::Sequential);
+    assert_eq!(expected.get(&'a'), Some(&49));
+    for strategy in frequency::Strategy::VALUES {
+        for workers in 1..=5 {
+            assert_eq!(
+                frequency::frequency_with(&v[..], workers, strategy),
+                expected,
+                "{strategy:?} with {workers} workers"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_all_strategies_agree_on_greek() {
+    let inputs: [&[&str]; 4] = [
+        &["ΟΔΟΣ"],
+        &["ΟΔΟΣa"],
+        &["aΣ"],
+        &["ΟΔΟΣ", "ΑΒ", "ΣΟΦΟΣ ΟΔΟΣ,", "aΣ ΟΔΟΣa", "Σ", "ΟΔΟΣ"],
+    ];
+    for input in inputs {
+        let expected = frequency::frequency_with(input, 1, frequency::Strategy::Sequential);
+        let text = input.join("\n").to_lowercase();
+        for letter in ['σ', 'ς'] {
+            let count = text.chars().filter(|&ch| ch == letter).count();
+            assert_eq!(expected.get(&letter).copied().unwrap_or(0), count, "{input:?}");
+        }
+        for strategy in frequency::Strategy::VALUES {
+            for workers in 1..=4 {
+                assert_eq!(
+                    frequency::frequency_with(input, workers, strategy),
+                    expected,
+                    "{strategy:?} with {workers} workers on {input:?}"
+                );
+            }
+        }
+    }
+}
+
 #[test]
 fn foo() {
     // #[derive(Debug)]